@@ -6,12 +6,212 @@ use serde::{Deserialize, Serialize};
 use crate::{
     spectra::Spectra,
     swell::{SwellProvider, SwellProviderError, SwellSummary},
-    tools::math::f_eq,
+    tools::{
+        partition::PartitionConfig,
+        spectral_estimation::{
+            is_missing_directional_value, polar_to_fourier, DirectionalCoefficients,
+            SpectralEstimationError,
+        },
+        vector::diff,
+    },
     units::direction,
 };
 
 use super::spectral_wave_data_record::SpectralWaveDataRecord;
 
+/// Method used to estimate the directional distribution from the first four directional Fourier
+/// coefficients reported by buoys
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub enum DirectionalEstimator {
+    /// Truncated Fourier series, negative lobes are clamped to zero
+    #[default]
+    FourierSeries,
+    /// Maximum Entropy Method (Lygre & Krogstad, 1986)
+    MaximumEntropy,
+    /// Maximum Likelihood Method (Oltman-Shay & Guza, 1984)
+    MaximumLikelihood,
+}
+
+impl DirectionalEstimator {
+    /// Directional distribution in 1/rad over the given direction bins in radians for a single
+    /// frequency band, given the mean and principal wave directions in degrees and the
+    /// first and second polar coefficients r1 and r2
+    pub fn distribution(
+        &self,
+        direction: &[f64],
+        mean_wave_direction: f64,
+        primary_wave_direction: f64,
+        first_polar_coefficient: f64,
+        second_polar_coefficient: f64,
+    ) -> Vec<f64> {
+        let alpha1 = mean_wave_direction.to_radians();
+        let alpha2 = primary_wave_direction.to_radians();
+        let r1 = first_polar_coefficient;
+        let r2 = second_polar_coefficient;
+        let (a1, b1, a2, b2) = polar_to_fourier(
+            mean_wave_direction,
+            primary_wave_direction,
+            first_polar_coefficient,
+            second_polar_coefficient,
+        );
+
+        let fourier_series = || {
+            direction
+                .iter()
+                .map(|angle| {
+                    let first = r1 * (angle - alpha1).cos();
+                    let second = r2 * (2.0 * (angle - alpha2)).cos();
+                    ((1.0 / PI) * (0.5 + first + second)).max(0.0)
+                })
+                .collect::<Vec<f64>>()
+        };
+
+        let distribution = match self {
+            DirectionalEstimator::FourierSeries => return fourier_series(),
+            DirectionalEstimator::MaximumEntropy => maximum_entropy(direction, a1, b1, a2, b2),
+            DirectionalEstimator::MaximumLikelihood => {
+                maximum_likelihood(direction, a1, b1, a2, b2)
+            }
+        };
+
+        // Degenerate coefficients (r1 or r2 at or above one) fall back to the fourier series
+        match distribution.and_then(|d| normalize_distribution(d, direction)) {
+            Some(d) => d,
+            None => fourier_series(),
+        }
+    }
+}
+
+/// Lygre & Krogstad maximum entropy estimate, unnormalized
+fn maximum_entropy(direction: &[f64], a1: f64, b1: f64, a2: f64, b2: f64) -> Option<Vec<f64>> {
+    let c1 = (a1, b1);
+    let c2 = (a2, b2);
+
+    let denom = 1.0 - (a1.powi(2) + b1.powi(2));
+    if denom <= f64::EPSILON {
+        return None;
+    }
+
+    // phi1 = (c1 - c2 * conj(c1)) / (1 - |c1|^2), phi2 = c2 - c1 * phi1
+    let c2c1 = complex_mul(c2, complex_conj(c1));
+    let phi1 = ((c1.0 - c2c1.0) / denom, (c1.1 - c2c1.1) / denom);
+    let c1phi1 = complex_mul(c1, phi1);
+    let phi2 = (c2.0 - c1phi1.0, c2.1 - c1phi1.1);
+
+    let numer = 1.0 - complex_mul(phi1, complex_conj(c1)).0 - complex_mul(phi2, complex_conj(c2)).0;
+
+    let distribution = direction
+        .iter()
+        .map(|angle| {
+            let e1 = complex_mul(phi1, (angle.cos(), -angle.sin()));
+            let e2 = complex_mul(phi2, ((2.0 * angle).cos(), -(2.0 * angle).sin()));
+            let d = (1.0 - e1.0 - e2.0, -e1.1 - e2.1);
+            numer / (2.0 * PI * (d.0.powi(2) + d.1.powi(2)))
+        })
+        .collect();
+
+    Some(distribution)
+}
+
+/// Maximum likelihood estimate using the normalized heave, pitch and roll cross spectral
+/// matrix, unnormalized
+fn maximum_likelihood(direction: &[f64], a1: f64, b1: f64, a2: f64, b2: f64) -> Option<Vec<f64>> {
+    let m = [
+        [1.0, a1, b1],
+        [a1, 0.5 * (1.0 + a2), 0.5 * b2],
+        [b1, 0.5 * b2, 0.5 * (1.0 - a2)],
+    ];
+
+    let det = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+    if det.abs() <= f64::EPSILON {
+        return None;
+    }
+
+    let mut inv = [[0.0; 3]; 3];
+    for (i, row) in inv.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            // Cofactor of the transposed element, the matrix is symmetric
+            let (r0, r1) = match j {
+                0 => (1, 2),
+                1 => (0, 2),
+                _ => (0, 1),
+            };
+            let (c0, c1) = match i {
+                0 => (1, 2),
+                1 => (0, 2),
+                _ => (0, 1),
+            };
+            let minor = m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+            let sign = if (i + j) % 2 == 0 { 1.0 } else { -1.0 };
+            *value = sign * minor / det;
+        }
+    }
+
+    direction
+        .iter()
+        .map(|angle| {
+            let w = [1.0, angle.cos(), angle.sin()];
+            let mut quad = 0.0;
+            for i in 0..3 {
+                for j in 0..3 {
+                    quad += w[i] * inv[i][j] * w[j];
+                }
+            }
+
+            if quad > 0.0 {
+                Some(1.0 / quad)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Scales the distribution so it integrates to one over the direction bins
+fn normalize_distribution(distribution: Vec<f64>, direction: &[f64]) -> Option<Vec<f64>> {
+    let dth = diff(direction);
+    let total = distribution
+        .iter()
+        .zip(dth.iter())
+        .map(|(d, dd)| d * dd)
+        .sum::<f64>();
+
+    if !total.is_finite() || total <= 0.0 {
+        return None;
+    }
+
+    Some(distribution.iter().map(|d| d / total).collect())
+}
+
+fn complex_mul(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+fn complex_conj(a: (f64, f64)) -> (f64, f64) {
+    (a.0, -a.1)
+}
+
+/// Missing values follow is_missing_directional_value, shared by the polar and fourier paths
+fn is_missing_coefficient(
+    energy_spectra: f64,
+    mean_wave_direction: f64,
+    primary_wave_direction: f64,
+    first_polar_coefficient: f64,
+    second_polar_coefficient: f64,
+) -> bool {
+    [
+        energy_spectra,
+        mean_wave_direction,
+        primary_wave_direction,
+        first_polar_coefficient,
+        second_polar_coefficient,
+    ]
+    .iter()
+    .any(|v| is_missing_directional_value(*v))
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DirectionalSpectralWaveDataRecord {
     pub date: DateTime<Utc>,
@@ -28,29 +228,56 @@ impl DirectionalSpectralWaveDataRecord {
         primary_wave_direction: &[f64],
         first_polar_coefficient: &[f64],
         second_polar_coefficient: &[f64],
+    ) -> Self {
+        Self::new_with_estimator(
+            date,
+            direction,
+            frequency,
+            energy_spectra,
+            mean_wave_direction,
+            primary_wave_direction,
+            first_polar_coefficient,
+            second_polar_coefficient,
+            &DirectionalEstimator::FourierSeries,
+        )
+    }
+
+    /// Rebuilds the directional spectra using the given directional estimator. Direction bins are in radians
+    pub fn new_with_estimator(
+        date: &DateTime<Utc>,
+        direction: &[f64],
+        frequency: &[f64],
+        energy_spectra: &[f64],
+        mean_wave_direction: &[f64],
+        primary_wave_direction: &[f64],
+        first_polar_coefficient: &[f64],
+        second_polar_coefficient: &[f64],
+        estimator: &DirectionalEstimator,
     ) -> Self {
         let mut directional_spectra = vec![0.0; frequency.len() * direction.len()];
 
         for (ik, _) in frequency.iter().enumerate() {
-            for (ith, angle) in direction.iter().enumerate() {
-                if f_eq(energy_spectra[ik], 999.9)
-                    || f_eq(mean_wave_direction[ik], 999.0)
-                    || f_eq(primary_wave_direction[ik], 999.0)
-                    || f_eq(first_polar_coefficient[ik], 999.0)
-                    || f_eq(second_polar_coefficient[ik], 999.0)
-                {
-                    continue;
-                }
-
-                let i = ik + (ith * frequency.len());
+            if is_missing_coefficient(
+                energy_spectra[ik],
+                mean_wave_direction[ik],
+                primary_wave_direction[ik],
+                first_polar_coefficient[ik],
+                second_polar_coefficient[ik],
+            ) {
+                continue;
+            }
 
-                let first = first_polar_coefficient[ik]
-                    * (angle - mean_wave_direction[ik].to_radians()).cos();
-                let second = second_polar_coefficient[ik]
-                    * (2.0 * (angle - primary_wave_direction[ik].to_radians())).cos();
+            let distribution = estimator.distribution(
+                direction,
+                mean_wave_direction[ik],
+                primary_wave_direction[ik],
+                first_polar_coefficient[ik],
+                second_polar_coefficient[ik],
+            );
 
-                let v = energy_spectra[ik] * (1.0 / PI) * (0.5 + first + second);
-                directional_spectra[i] = if v >= 0.0 { v } else { 0.0 };
+            for (ith, d) in distribution.iter().enumerate() {
+                let i = ik + (ith * frequency.len());
+                directional_spectra[i] = energy_spectra[ik] * d;
             }
         }
 
@@ -75,7 +302,27 @@ impl DirectionalSpectralWaveDataRecord {
         first_polar_coefficient: SpectralWaveDataRecord,
         second_polar_coefficient: SpectralWaveDataRecord,
     ) -> Self {
-        Self::new(
+        Self::from_data_records_with_estimator(
+            direction,
+            energy_spectra,
+            mean_wave_direction,
+            primary_wave_direction,
+            first_polar_coefficient,
+            second_polar_coefficient,
+            &DirectionalEstimator::FourierSeries,
+        )
+    }
+
+    pub fn from_data_records_with_estimator(
+        direction: &[f64],
+        energy_spectra: SpectralWaveDataRecord,
+        mean_wave_direction: SpectralWaveDataRecord,
+        primary_wave_direction: SpectralWaveDataRecord,
+        first_polar_coefficient: SpectralWaveDataRecord,
+        second_polar_coefficient: SpectralWaveDataRecord,
+        estimator: &DirectionalEstimator,
+    ) -> Self {
+        Self::new_with_estimator(
             &energy_spectra.date,
             direction,
             &energy_spectra.frequency,
@@ -84,8 +331,86 @@ impl DirectionalSpectralWaveDataRecord {
             &primary_wave_direction.value,
             &first_polar_coefficient.value,
             &second_polar_coefficient.value,
+            estimator,
         )
     }
+
+    /// Builds the directional spectra from the first four directional fourier coefficients
+    /// a1, b1, a2 and b2 in the NDBC frame. Coefficients from Spotter buoys must be built with
+    /// DirectionalCoefficients::from_cartesian. Direction bins are in radians
+    pub fn from_fourier_coefficients(
+        date: &DateTime<Utc>,
+        direction: &[f64],
+        coefficients: &DirectionalCoefficients,
+    ) -> Self {
+        Self::from_fourier_coefficients_with_estimator(
+            date,
+            direction,
+            coefficients,
+            &DirectionalEstimator::FourierSeries,
+        )
+    }
+
+    /// Builds the directional spectra from the first four directional fourier coefficients
+    /// using the given directional estimator. Direction bins are in radians
+    pub fn from_fourier_coefficients_with_estimator(
+        date: &DateTime<Utc>,
+        direction: &[f64],
+        coefficients: &DirectionalCoefficients,
+        estimator: &DirectionalEstimator,
+    ) -> Self {
+        Self::new_with_estimator(
+            date,
+            direction,
+            &coefficients.frequency,
+            &coefficients.energy,
+            &coefficients.mean_wave_direction(),
+            &coefficients.primary_wave_direction(),
+            &coefficients.first_polar_coefficient(),
+            &coefficients.second_polar_coefficient(),
+            estimator,
+        )
+    }
+
+    /// Estimates the directional spectra from raw heave, north and west displacement series in
+    /// meters sampled at the given rate in hz, using Welch averaging over segments of the given
+    /// length. Direction bins are in radians
+    pub fn from_displacements(
+        date: &DateTime<Utc>,
+        direction: &[f64],
+        heave: &[f64],
+        north: &[f64],
+        west: &[f64],
+        sample_rate: f64,
+        segment_length: usize,
+    ) -> Result<Self, SpectralEstimationError> {
+        let coefficients = DirectionalCoefficients::from_displacements(
+            heave,
+            north,
+            west,
+            sample_rate,
+            segment_length,
+        )?;
+        Ok(Self::from_fourier_coefficients(
+            date,
+            direction,
+            &coefficients,
+        ))
+    }
+
+    /// Extracts the swell components after partitioning the spectra with the given config
+    pub fn swell_data_with_config(
+        &self,
+        config: &PartitionConfig,
+    ) -> Result<SwellSummary, SwellProviderError> {
+        let partitions = self
+            .spectra
+            .partition_with_config(config, None, None, None)
+            .map_err(|_| {
+                SwellProviderError::SwellPartitionError("Failed to partition spectra".to_string())
+            })?;
+        self.spectra.swell_data(None, None, None, &partitions)
+    }
 }
 
 impl SwellProvider for DirectionalSpectralWaveDataRecord {
@@ -96,3 +421,169 @@ impl SwellProvider for DirectionalSpectralWaveDataRecord {
         self.spectra.swell_data(None, None, None, &partitions)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use chrono::Utc;
+
+    use super::{DirectionalEstimator, DirectionalSpectralWaveDataRecord};
+    use crate::tools::spectral_estimation::DirectionalCoefficients;
+
+    fn directions() -> Vec<f64> {
+        (0..72).map(|i| (i as f64 * 5.0).to_radians()).collect()
+    }
+
+    #[test]
+    fn test_estimator_normalization() {
+        let direction = directions();
+        let dth = 5.0f64.to_radians();
+
+        for estimator in [
+            DirectionalEstimator::FourierSeries,
+            DirectionalEstimator::MaximumEntropy,
+            DirectionalEstimator::MaximumLikelihood,
+        ] {
+            let d = estimator.distribution(&direction, 120.0, 120.0, 0.6, 0.3);
+            let total = d.iter().sum::<f64>() * dth;
+            assert!((total - 1.0).abs() < 0.01);
+
+            let (ipeak, _) = d
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+                .unwrap();
+            assert!((direction[ipeak].to_degrees() - 120.0).abs() <= 5.0);
+        }
+    }
+
+    #[test]
+    fn test_estimator_sharpness() {
+        let direction = directions();
+
+        let peak = |estimator: DirectionalEstimator| {
+            estimator
+                .distribution(&direction, 200.0, 200.0, 0.85, 0.65)
+                .iter()
+                .cloned()
+                .fold(0.0, f64::max)
+        };
+
+        let fourier = peak(DirectionalEstimator::FourierSeries);
+        assert!(peak(DirectionalEstimator::MaximumEntropy) > fourier);
+        assert!(peak(DirectionalEstimator::MaximumLikelihood) > fourier);
+    }
+
+    #[test]
+    fn test_estimator_degenerate_fallback() {
+        let direction = directions();
+
+        let fourier =
+            DirectionalEstimator::FourierSeries.distribution(&direction, 90.0, 90.0, 1.0, 1.0);
+        let mem =
+            DirectionalEstimator::MaximumEntropy.distribution(&direction, 90.0, 90.0, 1.0, 1.0);
+        assert_eq!(fourier, mem);
+        assert!(fourier.iter().all(|d| *d >= 0.0 && *d <= 2.5 / PI));
+    }
+
+    #[test]
+    fn test_from_displacements() {
+        let sample_rate = 2.0;
+        let toward = 70.0f64.to_radians();
+        let (heave, (north, west)): (Vec<f64>, (Vec<f64>, Vec<f64>)) = (0..2048)
+            .map(|i| {
+                let arg = 2.0 * PI * 0.1 * i as f64 / sample_rate;
+                (
+                    arg.cos(),
+                    (arg.sin() * toward.cos(), -arg.sin() * toward.sin()),
+                )
+            })
+            .unzip();
+
+        let record = DirectionalSpectralWaveDataRecord::from_displacements(
+            &Utc::now(),
+            &directions(),
+            &heave,
+            &north,
+            &west,
+            sample_rate,
+            256,
+        )
+        .unwrap();
+
+        // Waves travelling towards 70 degrees come from 250 degrees
+        let peak_direction = record.spectra.peak_direction(None).get_value();
+        assert!((peak_direction - 250.0).abs() <= 5.0);
+        assert!((record.spectra.peak_frequency(None).get_value() - 0.1).abs() < 0.01);
+
+        assert!(DirectionalSpectralWaveDataRecord::from_displacements(
+            &Utc::now(),
+            &directions(),
+            &heave,
+            &north[0..100],
+            &west,
+            sample_rate,
+            256,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_from_fourier_coefficients() {
+        let date = Utc::now();
+        let direction = directions();
+        let frequency = [0.08, 0.1, 0.12];
+        let energy = [1.0, 2.0, 999.9];
+        let mean_wave_direction = [100.0, 110.0, 999.0];
+        let primary_wave_direction = [105.0, 112.0, 999.0];
+        let r1 = [0.7, 0.8, 999.0];
+        let r2 = [0.4, 0.5, 999.0];
+
+        let polar = DirectionalSpectralWaveDataRecord::new(
+            &date,
+            &direction,
+            &frequency,
+            &energy,
+            &mean_wave_direction,
+            &primary_wave_direction,
+            &r1,
+            &r2,
+        );
+
+        let from_polar = DirectionalCoefficients::from_polar(
+            &frequency,
+            &energy,
+            &mean_wave_direction,
+            &primary_wave_direction,
+            &r1,
+            &r2,
+        );
+        let coefficients = DirectionalCoefficients::new(
+            &frequency,
+            &energy,
+            &from_polar.a1,
+            &from_polar.b1,
+            &from_polar.a2,
+            &from_polar.b2,
+        );
+        let fourier = DirectionalSpectralWaveDataRecord::from_fourier_coefficients(
+            &date,
+            &direction,
+            &coefficients,
+        );
+
+        for (a, b) in polar
+            .spectra
+            .energy
+            .iter()
+            .zip(fourier.spectra.energy.iter())
+        {
+            assert!((a - b).abs() < 1e-9);
+        }
+
+        // Missing frequencies are left empty on both paths
+        let nk = frequency.len();
+        assert!((0..direction.len()).all(|ith| fourier.spectra.energy[2 + ith * nk] == 0.0));
+    }
+}
@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::dimensional_data::DimensionalData;
 use crate::location::Location;
-use crate::swell::{Swell, SwellProvider, SwellSummary};
+use crate::swell::{total_wave_power, Swell, SwellProvider, SwellSummary};
 use crate::tools::waves::wave_energy;
 use crate::units::{Direction, Unit, UnitConvertible, UnitSystem};
 
@@ -129,6 +129,14 @@ pub struct ForecastCBulletinWaveRecord {
     pub swell_components: Vec<Swell>,
 }
 
+impl ForecastCBulletinWaveRecord {
+    /// Total wave power of the swell components in kW/m. When depth in meters is not supplied
+    /// use deep water approximation
+    pub fn wave_power(&self, depth: Option<f64>) -> DimensionalData<f64> {
+        total_wave_power(&self.swell_components, depth)
+    }
+}
+
 impl ParseableDataRecord for ForecastCBulletinWaveRecord {
     type Metadata = ForecastCBulletinWaveRecordMetadata;
 
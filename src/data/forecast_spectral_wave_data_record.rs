@@ -10,7 +10,8 @@ use serde::{Deserialize, Serialize};
 use crate::dimensional_data::DimensionalData;
 use crate::location::Location;
 use crate::spectra::Spectra;
-use crate::swell::{SwellProvider, SwellSummary};
+use crate::swell::{SwellProvider, SwellProviderError, SwellSummary};
+use crate::tools::partition::PartitionConfig;
 use crate::units::{direction, Direction, Unit, UnitConvertible, UnitSystem};
 
 use super::parseable_data_record::DataRecordParsingError;
@@ -141,7 +142,10 @@ impl FromStr for ForecastSpectralWaveDataRecordMetadata {
                     let value = v.parse::<f64>();
                     if let Ok(value) = value {
                         let angle = ((value - (2.5 * PI)) % (2.0 * PI)).abs();
-                        direction.push(Direction::from_radians(angle));
+                        // Bins are stored to three significant digits, so truncating the
+                        // degrees would bias most of them a degree low
+                        let degrees = angle.to_degrees().round() as i32 % 360;
+                        direction.push(Direction::from_degrees(degrees));
                         //direction.push(Direction::from_radians(value));
                     }
                 }
@@ -212,12 +216,37 @@ impl UnitConvertible for ForecastSpectralWaveDataRecord {
     }
 }
 
+impl ForecastSpectralWaveDataRecord {
+    /// Extracts the swell components after partitioning the spectra with the given config, using
+    /// the depth and wind from the record to separate wind sea
+    pub fn swell_data_with_config(
+        &self,
+        config: &PartitionConfig,
+    ) -> Result<SwellSummary, SwellProviderError> {
+        let partitions = self
+            .spectra
+            .partition_with_config(
+                config,
+                self.depth.value,
+                self.wind_speed.value,
+                self.wind_direction.value.as_ref().map(|d| d.degrees as f64),
+            )
+            .map_err(|_| {
+                SwellProviderError::SwellPartitionError("Failed to partition spectra".into())
+            })?;
+        self.spectra.swell_data(
+            self.depth.value,
+            self.wind_speed.value,
+            self.wind_direction.value.as_ref().map(|d| d.radian()),
+            &partitions,
+        )
+    }
+}
+
 impl SwellProvider for ForecastSpectralWaveDataRecord {
-    fn swell_data(&self) -> Result<SwellSummary, crate::swell::SwellProviderError> {
+    fn swell_data(&self) -> Result<SwellSummary, SwellProviderError> {
         let partitions = self.spectra.partition(100, None).map_err(|_| {
-            crate::swell::SwellProviderError::SwellPartitionError(
-                "Failed to partition spectra".into(),
-            )
+            SwellProviderError::SwellPartitionError("Failed to partition spectra".into())
         })?;
         self.spectra.swell_data(
             self.depth.value,
@@ -233,6 +262,8 @@ pub struct ForecastSpectralWaveRecordIterator<'a> {
     point_regex: Regex,
     metadata: ForecastSpectralWaveDataRecordMetadata,
     reference_date: Option<DateTime<Utc>>,
+    date: Option<DateTime<Utc>>,
+    remaining_points: usize,
 }
 
 impl<'a> ForecastSpectralWaveRecordIterator<'a> {
@@ -250,13 +281,14 @@ impl<'a> ForecastSpectralWaveRecordIterator<'a> {
             point_regex,
             metadata,
             reference_date: None,
+            date: None,
+            remaining_points: 0,
         })
     }
 
-    fn parse_next(&mut self) -> Result<ForecastSpectralWaveDataRecord, DataRecordParsingError> {
+    fn parse_date(&mut self) -> Result<DateTime<Utc>, DataRecordParsingError> {
         let line = self.lines.next().ok_or(DataRecordParsingError::EOF)?;
 
-        // First line is the date
         let year = line[0..4].parse::<i32>().map_err(|e| {
             DataRecordParsingError::ParseFailure(format!("Failed to parse year: {}", e))
         })?;
@@ -285,9 +317,32 @@ impl<'a> ForecastSpectralWaveRecordIterator<'a> {
             self.reference_date = Some(date);
         }
 
+        Ok(date)
+    }
+
+    fn parse_next(&mut self) -> Result<ForecastSpectralWaveDataRecord, DataRecordParsingError> {
+        // Every time step starts with the date followed by the data for each point
+        let date = match self.date {
+            Some(date) if self.remaining_points > 0 => date,
+            _ => {
+                let date = self.parse_date()?;
+                self.date = Some(date);
+                self.remaining_points = self.metadata.point_count.max(1);
+                date
+            }
+        };
+        self.remaining_points -= 1;
+
         let line = self.lines.next().ok_or(DataRecordParsingError::EOF)?;
 
-        // Then the point data
+        // Then the point data, the point name is only kept when it is quoted
+        let name = line
+            .trim_start()
+            .strip_prefix('\'')
+            .and_then(|rest| rest.split_once('\''))
+            .map(|(name, _)| name.trim().to_string())
+            .unwrap_or_default();
+
         let extracted: Result<(f64, f64, f64, f64, f64, f64, f64), DataRecordParsingError> =
             match self.point_regex.captures(line) {
                 Some(captures) => Ok((
@@ -394,7 +449,7 @@ impl<'a> ForecastSpectralWaveRecordIterator<'a> {
         Ok(ForecastSpectralWaveDataRecord {
             date,
             reference_date: self.reference_date.unwrap_or(date),
-            location: Location::new(latitude, longitude, "".into()),
+            location: Location::new(latitude, longitude, name),
             depth: DimensionalData {
                 value: Some(depth),
                 variable_name: "depth".into(),
@@ -464,10 +519,167 @@ impl<'a> ForecastSpectralWaveDataRecordCollection<'a> {
     }
 }
 
+/// Formats a value using the fortran Ew.d edit descriptor, ie 0.350E-01 for E10.3
+fn format_fortran_exponent(value: f64, width: usize, decimals: usize) -> String {
+    let (mantissa, exponent) = if value == 0.0 || !value.is_finite() {
+        (0.0, 0)
+    } else {
+        let mut exponent = value.abs().log10().floor() as i32 + 1;
+        let scale = 10f64.powi(decimals as i32);
+        let mut mantissa = (value.abs() / 10f64.powi(exponent) * scale).round() / scale;
+        if mantissa >= 1.0 {
+            mantissa /= 10.0;
+            exponent += 1;
+        }
+        (mantissa, exponent)
+    };
+
+    let sign = if value < 0.0 { "-" } else { "" };
+    let exponent_sign = if exponent < 0 { '-' } else { '+' };
+    let formatted = format!(
+        "{sign}{mantissa:.decimals$}E{exponent_sign}{:02}",
+        exponent.abs()
+    );
+    format!("{formatted:>width$}")
+}
+
+/// Writes the values using the given fortran Ew.d edit descriptor with the given count per line
+fn write_fortran_exponent_lines(
+    output: &mut String,
+    values: impl Iterator<Item = f64>,
+    per_line: usize,
+    width: usize,
+    decimals: usize,
+) {
+    let values = values.collect::<Vec<f64>>();
+    for chunk in values.chunks(per_line) {
+        chunk
+            .iter()
+            .for_each(|v| output.push_str(&format_fortran_exponent(*v, width, decimals)));
+        output.push('\n');
+    }
+}
+
+/// Serializes the given records to the WAVEWATCH III ASCII point spectra format. Consecutive records
+/// with the same date are written as the points of a single time step, so every time step must
+/// contain the same number of points and every spectra must share the same frequency and
+/// direction bins. Values are written in metric units with the three significant digits of the
+/// format, and the parser rounds direction bins to whole degrees, so only spectra whose direction
+/// bins fall on whole degrees read back with the same bins.
+pub fn write_forecast_spectral_wave_data(
+    records: &[ForecastSpectralWaveDataRecord],
+) -> Result<String, DataRecordParsingError> {
+    let first = records.first().ok_or(DataRecordParsingError::InvalidData)?;
+
+    let mut time_steps: Vec<&[ForecastSpectralWaveDataRecord]> = Vec::new();
+    let mut start = 0;
+    for i in 1..=records.len() {
+        if i == records.len() || records[i].date != records[start].date {
+            time_steps.push(&records[start..i]);
+            start = i;
+        }
+    }
+    let point_count = time_steps[0].len();
+    if time_steps.iter().any(|step| step.len() != point_count) {
+        return Err(DataRecordParsingError::ParseFailure(
+            "Every time step must have the same number of points".into(),
+        ));
+    }
+
+    // WW3 spectra are stored with the directions the parser maps to the Met convention
+    let spectra = records
+        .iter()
+        .map(|r| match r.spectra.dir_convention {
+            direction::DirectionConvention::Met => r.spectra.clone(),
+            _ => r
+                .spectra
+                .convert_convention(direction::DirectionConvention::Met),
+        })
+        .collect::<Vec<Spectra>>();
+
+    let frequency = &first.spectra.frequency;
+    let direction = spectra[0].direction_raw();
+    if spectra.iter().any(|s| {
+        &s.frequency != frequency
+            || s.direction_raw().len() != direction.len()
+            || s.direction_raw()
+                .iter()
+                .zip(direction.iter())
+                .any(|(a, b)| (a - b).abs() > 1e-9)
+    }) {
+        return Err(DataRecordParsingError::ParseFailure(
+            "Every spectra must share the same frequency and direction bins".into(),
+        ));
+    }
+
+    let mut output = format!(
+        "'WAVEWATCH III SPECTRA'{:6}{:6}{:6} 'spectral resolution for points'\n",
+        frequency.len(),
+        direction.len(),
+        point_count
+    );
+
+    write_fortran_exponent_lines(&mut output, frequency.iter().cloned(), 8, 10, 3);
+    write_fortran_exponent_lines(
+        &mut output,
+        direction.iter().map(|d| {
+            let degrees = (450.0 - d.to_degrees()).rem_euclid(360.0);
+            degrees.to_radians()
+        }),
+        7,
+        11,
+        3,
+    );
+
+    let mut spectra = spectra.iter();
+    for step in time_steps {
+        output.push_str(&format!("{}\n", step[0].date.format("%Y%m%d %H%M%S")));
+
+        for record in step {
+            let mut record = record.clone();
+            record.to_units(&UnitSystem::Metric);
+
+            let name = record.location.name.chars().take(10).collect::<String>();
+            output.push_str(&format!(
+                "'{:<10}'{:7.2}{:7.2}{:10.1}{:7.2}{:6.1}{:7.2}{:6.1}\n",
+                name,
+                record.location.latitude,
+                record.location.longitude,
+                record.depth.get_value(),
+                record.wind_speed.get_value(),
+                record.wind_direction.get_value().degrees as f64,
+                record.current_speed.get_value(),
+                record.current_direction.get_value().degrees as f64,
+            ));
+
+            let spectra = spectra.next().ok_or(DataRecordParsingError::InvalidData)?;
+            write_fortran_exponent_lines(&mut output, spectra.energy.iter().cloned(), 7, 11, 3);
+        }
+    }
+
+    Ok(output)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_forecast_spectra_direction_bins() {
+        let metadata = "'WAVEWATCH III SPECTRA'     4     4     1 'spectral resolution for points'
+ 0.350E-01 0.600E-01 0.103E+00 0.178E+00
+  0.785E+00  0.236E+01  0.393E+01  0.550E+01";
+
+        let metadata = ForecastSpectralWaveDataRecordMetadata::from_str(metadata).unwrap();
+
+        let degrees = metadata
+            .direction
+            .iter()
+            .map(|d| d.degrees)
+            .collect::<Vec<i32>>();
+        assert_eq!(degrees, vec![45, 315, 225, 135]);
+    }
+
     #[test]
     fn parse_forecast_spectra_metadata() {
         let metadata = "'WAVEWATCH III SPECTRA'     50    36     1 'spectral resolution for points'
@@ -498,4 +710,122 @@ mod tests {
         // assert_eq!(metadata.direction[0].degrees, 85);
         // assert_eq!(metadata.direction[15].degrees, 295);
     }
+
+    #[test]
+    fn parse_forecast_spectra_unquoted_point() {
+        let data = "'WAVEWATCH III SPECTRA'     2     2     1 'spectral resolution for points'
+ 0.350E-01 0.600E-01
+  0.785E+00  0.393E+01
+20220918 000000
+44097         40.97 -71.13      46.5   5.49 222.0   0.00 270.0
+  0.100E+00  0.200E+00  0.300E+00  0.400E+00
+";
+
+        let records = ForecastSpectralWaveRecordIterator::from_data(data)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].location.name, "");
+        assert_eq!(records[0].location.latitude, 40.97);
+        assert_eq!(records[0].location.longitude, -71.13);
+        assert_eq!(records[0].depth.value, Some(46.5));
+        assert_eq!(records[0].spectra.energy[3], 0.4);
+    }
+
+    #[test]
+    fn write_forecast_spectra_round_trip() {
+        let data = "'WAVEWATCH III SPECTRA'     4     4     2 'spectral resolution for points'
+ 0.350E-01 0.600E-01 0.103E+00 0.178E+00
+  0.785E+00  0.236E+01  0.393E+01  0.550E+01
+20220918 000000
+'44097     '  40.97 -71.13      46.5   5.49 222.0   0.00 270.0
+  0.000E+00  0.123E-02  0.456E+01  0.789E-01  0.100E+00  0.200E+00  0.300E+00
+  0.400E+00  0.500E+00  0.600E+00  0.700E+00  0.800E+00  0.900E+00  0.110E+01
+  0.120E+01  0.130E+01
+'44013     '  42.35 -70.65      64.0  10.20  45.0   0.10  90.0
+  0.130E+01  0.120E+01  0.110E+01  0.900E+00  0.800E+00  0.700E+00  0.600E+00
+  0.500E+00  0.400E+00  0.300E+00  0.200E+00  0.100E+00  0.789E-01  0.456E+01
+  0.123E-02  0.000E+00
+20220918 030000
+'44097     '  40.97 -71.13      46.5   6.01 230.0   0.00 270.0
+  0.100E-02  0.200E-02  0.300E-02  0.400E-02  0.500E-02  0.600E-02  0.700E-02
+  0.800E-02  0.900E-02  0.100E-01  0.110E-01  0.120E-01  0.130E-01  0.140E-01
+  0.150E-01  0.160E-01
+'44013     '  42.35 -70.65      64.0   9.80  50.0   0.10  90.0
+  0.160E-01  0.150E-01  0.140E-01  0.130E-01  0.120E-01  0.110E-01  0.100E-01
+  0.900E-02  0.800E-02  0.700E-02  0.600E-02  0.500E-02  0.400E-02  0.300E-02
+  0.200E-02  0.100E-02
+";
+
+        let records = ForecastSpectralWaveRecordIterator::from_data(data)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[1].location.name, "44013");
+        assert_eq!(records[1].date, records[0].date);
+        assert_eq!(records[3].spectra.energy[0], 0.016);
+
+        let written = write_forecast_spectral_wave_data(&records).unwrap();
+        assert_eq!(written, data);
+
+        let reparsed = ForecastSpectralWaveRecordIterator::from_data(&written)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        for (a, b) in records.iter().zip(reparsed.iter()) {
+            assert_eq!(a.date, b.date);
+            assert_eq!(a.location, b.location);
+            assert_eq!(a.spectra.frequency, b.spectra.frequency);
+            assert_eq!(a.spectra.direction_raw(), b.spectra.direction_raw());
+            assert_eq!(a.spectra.energy, b.spectra.energy);
+            assert_eq!(a.wind_speed.value, b.wind_speed.value);
+            assert_eq!(
+                a.wind_direction.get_value().degrees,
+                b.wind_direction.get_value().degrees
+            );
+        }
+    }
+
+    #[test]
+    fn write_forecast_spectra_converts_convention() {
+        let data = "'WAVEWATCH III SPECTRA'     4     4     1 'spectral resolution for points'
+ 0.350E-01 0.600E-01 0.103E+00 0.178E+00
+  0.785E+00  0.236E+01  0.393E+01  0.550E+01
+20220918 000000
+'44097     '  40.97 -71.13      46.5   5.49 222.0   0.00 270.0
+  0.000E+00  0.123E-02  0.456E+01  0.789E-01  0.100E+00  0.200E+00  0.300E+00
+  0.400E+00  0.500E+00  0.600E+00  0.700E+00  0.800E+00  0.900E+00  0.110E+01
+  0.120E+01  0.130E+01
+";
+
+        let mut record = ForecastSpectralWaveRecordIterator::from_data(data)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        let original = record.spectra.clone();
+        record.spectra = record
+            .spectra
+            .convert_convention(direction::DirectionConvention::From);
+
+        let written = write_forecast_spectral_wave_data(&[record]).unwrap();
+        let reparsed = ForecastSpectralWaveRecordIterator::from_data(&written)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+
+        // Physical directions survive even though the bins come back in sorted order
+        let converted = reparsed
+            .spectra
+            .convert_convention(direction::DirectionConvention::From);
+        let expected = original.convert_convention(direction::DirectionConvention::From);
+        assert_eq!(converted.direction_raw(), expected.direction_raw());
+        assert_eq!(converted.energy, expected.energy);
+    }
 }
@@ -8,8 +8,12 @@ use crate::{
     dimensional_data::DimensionalData,
     location::Location,
     model::NOAAModel,
-    swell::Swell,
-    tools::waves::wave_energy,
+    spectra::Spectra,
+    swell::{total_wave_power, Swell},
+    tools::{
+        parametric::{DirectionalSpreading, SpectralShape},
+        waves::wave_energy,
+    },
     units::{Direction, Unit, UnitConvertible, UnitSystem},
 };
 
@@ -22,6 +26,9 @@ pub struct GFSWaveGribPointDataRecord {
     pub wind_speed: DimensionalData<f64>,
     pub wind_direction: DimensionalData<Direction>,
     pub swell_components: Vec<Swell>,
+    /// Index of the wind sea in the swell components, when the record has one
+    #[serde(default)]
+    pub wind_sea_index: Option<usize>,
 }
 
 impl GFSWaveGribPointDataRecord {
@@ -128,6 +135,7 @@ impl GFSWaveGribPointDataRecord {
             }
         }
 
+        let mut wind_sea_index = None;
         if data.contains_key("WVHGT")
             && data.contains_key("WVPER")
             && data.contains_key("WVDIR")
@@ -145,15 +153,21 @@ impl GFSWaveGribPointDataRecord {
                 None,
             );
 
+            wind_sea_index = Some(swell_components.len());
             swell_components.push(component);
         };
 
-        swell_components.sort_by(|a, b| {
-            b.wave_height
+        // Sort the components by wave height, following the wind sea to its new position
+        let mut order = (0..swell_components.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            swell_components[*b]
+                .wave_height
                 .get_value()
-                .partial_cmp(&a.wave_height.get_value())
+                .partial_cmp(&swell_components[*a].wave_height.get_value())
                 .unwrap()
         });
+        let wind_sea_index = wind_sea_index.and_then(|w| order.iter().position(|i| *i == w));
+        let swell_components = order.iter().map(|i| swell_components[*i].clone()).collect();
 
         Ok(GFSWaveGribPointDataRecord {
             date,
@@ -161,8 +175,50 @@ impl GFSWaveGribPointDataRecord {
             wind_speed,
             wind_direction,
             swell_components,
+            wind_sea_index,
         })
     }
+
+    /// The wind sea component, when the record has one
+    pub fn wind_sea(&self) -> Option<&Swell> {
+        self.wind_sea_index
+            .and_then(|index| self.swell_components.get(index))
+    }
+
+    /// Rebuilds an approximate two dimensional spectra on the given frequency (hz) and
+    /// direction (degrees) grid by superposing a parametric spectrum for every partition.
+    /// Swell partitions use a narrow JONSWAP shape while the wind sea uses a standard JONSWAP
+    /// with broader spreading. Each partition keeps its own energy and peak direction.
+    pub fn to_spectra(&self, frequency: &[f64], direction: &[f64]) -> Spectra {
+        let components = self
+            .swell_components
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                if self.wind_sea_index == Some(i) {
+                    (
+                        c.clone(),
+                        SpectralShape::Jonswap { gamma: 3.3 },
+                        DirectionalSpreading::CosineSquared { s: 10.0 },
+                    )
+                } else {
+                    (
+                        c.clone(),
+                        SpectralShape::Jonswap { gamma: 6.0 },
+                        DirectionalSpreading::CosineSquared { s: 40.0 },
+                    )
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Spectra::from_parametric_components(frequency, direction, &components)
+    }
+
+    /// Total wave power of the swell components, including the wind sea, in kW/m. When depth
+    /// in meters is not supplied use deep water approximation
+    pub fn wave_power(&self, depth: Option<f64>) -> DimensionalData<f64> {
+        total_wave_power(&self.swell_components, depth)
+    }
 }
 
 impl UnitConvertible for GFSWaveGribPointDataRecord {
@@ -176,3 +232,108 @@ impl UnitConvertible for GFSWaveGribPointDataRecord {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::GFSWaveGribPointDataRecord;
+    use crate::{
+        dimensional_data::DimensionalData,
+        swell::Swell,
+        units::{Direction, Unit, UnitSystem},
+    };
+
+    fn record(
+        swell_components: Vec<Swell>,
+        wind_sea_index: Option<usize>,
+    ) -> GFSWaveGribPointDataRecord {
+        GFSWaveGribPointDataRecord {
+            date: Utc::now(),
+            wave_summary: swell_components[0].clone(),
+            wind_speed: DimensionalData {
+                value: Some(10.0),
+                variable_name: "wind speed".into(),
+                unit: Unit::MetersPerSecond,
+            },
+            wind_direction: DimensionalData {
+                value: Some(Direction::from_degrees(90)),
+                variable_name: "wind directions".into(),
+                unit: Unit::Degrees,
+            },
+            swell_components,
+            wind_sea_index,
+        }
+    }
+
+    fn component(wave_height: f64, period: f64, direction: i32) -> Swell {
+        Swell::new(
+            &UnitSystem::Metric,
+            wave_height,
+            period,
+            Direction::from_degrees(direction),
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_to_spectra() {
+        let frequency = (0..40).map(|i| 0.035 * 1.07f64.powi(i)).collect::<Vec<_>>();
+        let direction = (0..36).map(|i| i as f64 * 10.0).collect::<Vec<_>>();
+
+        let gfs = record(
+            vec![component(2.0, 14.0, 270), component(1.0, 5.0, 90)],
+            Some(1),
+        );
+        assert_eq!(gfs.wind_sea().unwrap().period.get_value(), 5.0);
+
+        // Every partition keeps its own energy and peak direction
+        let spectra = gfs.to_spectra(&frequency, &direction);
+        let hs = spectra.hm0(None).get_value();
+        assert!((hs - 5.0f64.sqrt()).abs() < 1e-6);
+        assert!((spectra.hm0(Some((0.0, 0.1))).get_value() - 2.0).abs() < 0.15);
+        assert!((spectra.hm0(Some((0.15, 1.0))).get_value() - 1.0).abs() < 0.15);
+        assert!((spectra.peak_direction(Some((0.0, 0.1))).get_value() - 270.0).abs() < 0.5);
+        assert!((spectra.peak_direction(Some((0.15, 1.0))).get_value() - 90.0).abs() < 5.0);
+
+        // A swell identical to the wind sea keeps the narrow swell shape
+        let twins = record(
+            vec![component(1.0, 8.0, 180), component(1.0, 8.0, 180)],
+            Some(1),
+        );
+        let swell_only = record(
+            vec![component(1.0, 8.0, 180), component(1.0, 8.0, 180)],
+            None,
+        );
+        let twins_spread = twins
+            .to_spectra(&frequency, &direction)
+            .directional_spread(None)
+            .get_value();
+        let swell_spread = swell_only
+            .to_spectra(&frequency, &direction)
+            .directional_spread(None)
+            .get_value();
+        assert!(twins_spread > swell_spread);
+    }
+
+    #[test]
+    fn test_wave_power() {
+        let components = vec![component(2.0, 14.0, 270), component(1.0, 5.0, 90)];
+        let gfs = record(components.clone(), Some(1));
+
+        // The wind sea is one of the components and is only counted once
+        let expected = components
+            .iter()
+            .map(|c| c.wave_power(None).get_value())
+            .sum::<f64>();
+        let power = gfs.wave_power(None);
+        assert!((power.get_value() - expected).abs() < 1e-9);
+        assert_eq!(power.unit, Unit::KiloWattsPerMeter);
+        assert_eq!(
+            power.get_value(),
+            record(components, None).wave_power(None).get_value()
+        );
+    }
+}
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     dimensional_data::DimensionalData,
+    swell::Swell,
+    tools::{wave_growth::WaveGrowthModel, waves::Error},
     units::{Direction, Unit},
 };
 
@@ -110,6 +112,43 @@ impl From<&NwsWeatherForecastPeriodData> for NwsWeatherForecastDataRecord {
     }
 }
 
+impl NwsWeatherForecastDataRecord {
+    /// Predicts the wind waves generated by the forecast wind blowing over the given fetch in
+    /// meters for the given duration in seconds
+    pub fn wind_wave_growth(
+        &self,
+        fetch: f64,
+        duration: f64,
+        model: &WaveGrowthModel,
+    ) -> Result<Swell, Error> {
+        let wind_speed = self
+            .wind_speed
+            .value
+            .ok_or_else(|| Error::MissingData("wind speed".to_string()))?;
+        let wind_direction = self
+            .wind_direction
+            .value
+            .clone()
+            .ok_or_else(|| Error::MissingData("wind direction".to_string()))?;
+
+        let wind_speed = match self.wind_speed.unit {
+            Unit::MetersPerSecond => wind_speed,
+            Unit::MilesPerHour | Unit::Knots => self
+                .wind_speed
+                .unit
+                .convert(wind_speed, &Unit::MetersPerSecond),
+            _ => {
+                return Err(Error::InvalidParameter(format!(
+                    "unsupported wind speed unit {:?}",
+                    self.wind_speed.unit
+                )))
+            }
+        };
+
+        model.swell(wind_speed, wind_direction, fetch, duration)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct NwsWeatherForecastDataRecordCollectionProperties {
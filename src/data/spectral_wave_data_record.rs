@@ -2,6 +2,14 @@ use chrono::{DateTime, TimeZone, Utc};
 use csv::Reader;
 use serde::{Deserialize, Serialize};
 
+use crate::dimensional_data::DimensionalData;
+use crate::spectra::bulk_data;
+use crate::tools::math::is_missing_value;
+use crate::tools::statistics::{
+    expected_max_wave_height, goda_peakedness, rayleigh_h1_10, rayleigh_h1_3, spectral_bandwidth,
+    spectral_moment, spectral_narrowness, wave_group_length,
+};
+use crate::tools::vector::diff;
 use crate::units::*;
 
 use super::parseable_data_record::{DataRecordParsingError, ParseableDataRecord};
@@ -71,6 +79,97 @@ impl ParseableDataRecord for SpectralWaveDataRecord {
     }
 }
 
+/// Shape and height statistics of energy density records, where the values are in m2/hz
+impl SpectralWaveDataRecord {
+    /// Frequency bins, values and bin widths with the missing NDBC values left out, the bin
+    /// widths come from the full frequency grid so a dropped bin does not widen its neighbors
+    fn valid_bins(&self) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+        let df = diff(&self.frequency);
+        let mut frequency = Vec::with_capacity(self.frequency.len());
+        let mut value = Vec::with_capacity(self.value.len());
+        let mut bin_width = Vec::with_capacity(df.len());
+        for ((f, v), d) in self.frequency.iter().zip(&self.value).zip(&df) {
+            if is_missing_value(*v) {
+                continue;
+            }
+            frequency.push(*f);
+            value.push(*v);
+            bin_width.push(*d);
+        }
+
+        (frequency, value, bin_width)
+    }
+
+    fn m0(&self) -> f64 {
+        let (frequency, value, df) = self.valid_bins();
+        spectral_moment(&frequency, &value, &df, 0)
+    }
+
+    /// Goda peakedness parameter Qp
+    pub fn goda_peakedness(&self) -> DimensionalData<f64> {
+        let (frequency, value, df) = self.valid_bins();
+        let value = goda_peakedness(&frequency, &value, &df);
+        bulk_data(value, "peakedness", Unit::Dimensionless)
+    }
+
+    /// Longuet-Higgins spectral bandwidth nu
+    pub fn spectral_bandwidth(&self) -> DimensionalData<f64> {
+        let (frequency, value, df) = self.valid_bins();
+        let value = spectral_bandwidth(&frequency, &value, &df);
+        bulk_data(value, "spectral bandwidth", Unit::Dimensionless)
+    }
+
+    /// Spectral narrowness epsilon
+    pub fn spectral_narrowness(&self) -> DimensionalData<f64> {
+        let (frequency, value, df) = self.valid_bins();
+        let value = spectral_narrowness(&frequency, &value, &df);
+        bulk_data(value, "spectral narrowness", Unit::Dimensionless)
+    }
+
+    /// Expected number of waves higher than the significant wave height in a group
+    pub fn wave_group_length(&self) -> DimensionalData<f64> {
+        let (frequency, value, df) = self.valid_bins();
+        let value = wave_group_length(&frequency, &value, &df);
+        bulk_data(value, "wave group length", Unit::Dimensionless)
+    }
+
+    /// Rayleigh based mean height of the highest third of the waves in meters
+    pub fn h1_3(&self) -> DimensionalData<f64> {
+        let m0 = self.m0();
+        let value = if m0 > 0.0 {
+            Some(rayleigh_h1_3(m0))
+        } else {
+            None
+        };
+        bulk_data(value, "significant wave height", Unit::Meters)
+    }
+
+    /// Rayleigh based mean height of the highest tenth of the waves in meters
+    pub fn h1_10(&self) -> DimensionalData<f64> {
+        let m0 = self.m0();
+        let value = if m0 > 0.0 {
+            Some(rayleigh_h1_10(m0))
+        } else {
+            None
+        };
+        bulk_data(value, "highest tenth wave height", Unit::Meters)
+    }
+
+    /// Rayleigh based expected maximum wave height in meters for a storm lasting the given
+    /// duration in seconds
+    pub fn hmax(&self, duration: f64) -> DimensionalData<f64> {
+        let (frequency, value, df) = self.valid_bins();
+        let m0 = spectral_moment(&frequency, &value, &df, 0);
+        let m2 = spectral_moment(&frequency, &value, &df, 2);
+        let value = if m0 > 0.0 && m2 > 0.0 {
+            expected_max_wave_height(m0, (m0 / m2).sqrt(), duration)
+        } else {
+            None
+        };
+        bulk_data(value, "maximum wave height", Unit::Meters)
+    }
+}
+
 impl UnitConvertible for SpectralWaveDataRecord {
     fn to_units(&mut self, _: &UnitSystem) -> &mut Self {
         // TODO: Maybe some conversion
@@ -144,4 +243,29 @@ mod tests {
 
         assert!(spectral_data.separation_frequency.is_none());
     }
+
+    #[test]
+    fn test_spectral_wave_energy_statistics() {
+        let raw_data = "2018 09 01 10 00 9.999 0.000 (0.033) 0.000 (0.038) 0.000 (0.043) 0.000 (0.048) 0.000 (0.053) 0.000 (0.058) 0.000 (0.063) 0.021 (0.068) 0.021 (0.073) 0.074 (0.078) 0.085 (0.083) 0.074 (0.088) 0.085 (0.093) 0.085 (0.100) 0.148 (0.110) 0.138 (0.120) 0.074 (0.130) 0.244 (0.140) 0.392 (0.150) 0.477 (0.160) 0.572 (0.170) 1.060 (0.180) 0.339 (0.190) 0.382 (0.200) 0.265 (0.210) 0.265 (0.220) 0.318 (0.230) 0.329 (0.240) 0.329 (0.250) 0.350 (0.260) 0.244 (0.270) 0.371 (0.280) 0.180 (0.290) 0.180 (0.300) 0.170 (0.310) 0.117 (0.320) 0.127 (0.330) 0.095 (0.340) 0.064 (0.350) 0.085 (0.365) 0.085 (0.385) 0.074 (0.405) 0.021 (0.425) 0.011 (0.445) 0.021 (0.465) 0.011 (0.485)";
+        let data_row: Vec<&str> = raw_data.split_whitespace().collect();
+        let spectral_data = SpectralWaveDataRecord::from_data_row(None, &data_row).unwrap();
+
+        let h1_3 = spectral_data.h1_3().get_value();
+        let h1_10 = spectral_data.h1_10().get_value();
+        let hmax = spectral_data.hmax(3.0 * 3600.0).get_value();
+        assert!(h1_3 > 0.0 && h1_10 > h1_3 && hmax > h1_10);
+
+        assert!(spectral_data.goda_peakedness().get_value() > 0.0);
+        let nu = spectral_data.spectral_bandwidth().get_value();
+        assert!(nu > 0.0 && nu < 1.0);
+        assert!(spectral_data.spectral_narrowness().get_value() < 1.0);
+        assert!(spectral_data.wave_group_length().get_value() > 0.0);
+
+        // Missing values are left out of the statistics instead of swamping them
+        let mut missing_data = spectral_data.clone();
+        missing_data.value[0] = 999.0;
+        missing_data.value[1] = 999.9;
+        assert!((missing_data.h1_3().get_value() - h1_3).abs() < 1e-9);
+        assert!((missing_data.spectral_bandwidth().get_value() - nu).abs() < 1e-9);
+    }
 }
@@ -3,8 +3,10 @@ pub mod data;
 pub mod dimensional_data;
 pub mod location;
 pub mod model;
+pub mod runup;
 pub mod solar;
 pub mod spectra;
+pub mod spectra_time_series;
 pub mod station;
 pub mod swell;
 pub mod tide_station;
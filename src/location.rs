@@ -77,24 +77,58 @@ impl Location {
         }
     }
 
+    /// Great circle distance to the other location, in kilometers for metric units and miles
+    /// for english units
     pub fn distance(&self, other: &Location, unit: &UnitSystem) -> f64 {
-        let source_lat = self.absolute_latitude().to_radians();
-        let source_lon = self.absolute_longitude().to_radians();
-        let dest_lat = other.absolute_latitude().to_radians();
-        let dest_lon = other.absolute_longitude().to_radians();
+        let source_lat = self.relative_latitude().to_radians();
+        let source_lon = self.relative_longitude().to_radians();
+        let dest_lat = other.relative_latitude().to_radians();
+        let dest_lon = other.relative_longitude().to_radians();
 
         // Compute using the haversine formula
         let d_lat = dest_lat - source_lat;
         let d_lon = dest_lon - source_lon;
 
-        let a = (d_lat * 0.5).powf(2.0).sin()
-            + source_lat.cos() * dest_lat.cos() * (d_lon * 0.5).powf(2.0).sin();
-        let c = 2.0 * a.sqrt().asin();
+        let a = (d_lat * 0.5).sin().powi(2)
+            + source_lat.cos() * dest_lat.cos() * (d_lon * 0.5).sin().powi(2);
+        let c = 2.0 * a.sqrt().min(1.0).asin();
         let r = unit.earths_radius();
 
         c * r
     }
 
+    /// Initial great circle bearing to the other location in degrees clockwise from north
+    pub fn bearing_to(&self, other: &Location) -> f64 {
+        let source_lat = self.relative_latitude().to_radians();
+        let dest_lat = other.relative_latitude().to_radians();
+        let d_lon = (other.relative_longitude() - self.relative_longitude()).to_radians();
+
+        let y = d_lon.sin() * dest_lat.cos();
+        let x = source_lat.cos() * dest_lat.sin() - source_lat.sin() * dest_lat.cos() * d_lon.cos();
+        (y.atan2(x).to_degrees() + 360.0) % 360.0
+    }
+
+    /// The location reached by travelling the given distance along the great circle starting at
+    /// the given bearing in degrees clockwise from north. The distance is in kilometers for
+    /// metric units and miles for english units
+    pub fn destination(&self, bearing: f64, distance: f64, unit: &UnitSystem) -> Location {
+        let lat = self.relative_latitude().to_radians();
+        let lon = self.relative_longitude().to_radians();
+        let bearing = bearing.to_radians();
+        let delta = distance / unit.earths_radius();
+
+        let dest_lat = (lat.sin() * delta.cos() + lat.cos() * delta.sin() * bearing.cos()).asin();
+        let dest_lon = lon
+            + (bearing.sin() * delta.sin() * lat.cos())
+                .atan2(delta.cos() - lat.sin() * dest_lat.sin());
+
+        Location::new(
+            dest_lat.to_degrees(),
+            (dest_lon.to_degrees() + 540.0) % 360.0 - 180.0,
+            String::new(),
+        )
+    }
+
     pub fn within_bbox(&self, bbox: &(f64, f64, f64, f64)) -> bool {
         let within_lng = absolute_longitude(bbox.0) <= absolute_longitude(self.longitude)
             && absolute_longitude(self.longitude) <= absolute_longitude(bbox.2);
@@ -109,6 +143,7 @@ mod tests {
     use crate::location::{absolute_longitude, normalize_latitude};
 
     use super::{normalize_longitude, Location};
+    use crate::units::UnitSystem;
 
     #[test]
     fn test_normalize_coords() {
@@ -161,4 +196,43 @@ mod tests {
         let bbox = (0.0, -90.0, 359.75, 90.0);
         assert!(location.within_bbox(&bbox));
     }
+
+    #[test]
+    fn test_destination() {
+        let location = Location::new(0.0, 0.0, "".into());
+
+        // A quarter of the way around the equator
+        let quarter = std::f64::consts::PI * 6371.0 / 2.0;
+        let east = location.destination(90.0, quarter, &UnitSystem::Metric);
+        assert!(east.latitude.abs() < 1e-9);
+        assert!((east.longitude - 90.0).abs() < 1e-9);
+
+        let north = location.destination(0.0, quarter, &UnitSystem::Metric);
+        assert!((north.latitude - 90.0).abs() < 1e-9);
+
+        // Crosses the antimeridian
+        let location = Location::new(10.0, 179.0, "".into());
+        let west = location.destination(90.0, 500.0, &UnitSystem::Metric);
+        assert!(west.longitude < -170.0);
+    }
+
+    #[test]
+    fn test_distance_and_bearing() {
+        let new_york = Location::new(40.7128, -74.0060, "New York".into());
+        let london = Location::new(51.5074, -0.1278, "London".into());
+
+        let distance = new_york.distance(&london, &UnitSystem::Metric);
+        assert!((distance - 5570.0).abs() < 10.0);
+
+        let bearing = new_york.bearing_to(&london);
+        assert!((bearing - 51.2).abs() < 0.5);
+
+        // Travelling the distance along the bearing lands at the destination
+        let destination = new_york.destination(bearing, distance, &UnitSystem::Metric);
+        assert!((destination.latitude - london.latitude).abs() < 1e-6);
+        assert!((destination.longitude - london.longitude).abs() < 1e-6);
+
+        let south = Location::new(-33.9, 151.2, "Sydney".into());
+        assert!((south.bearing_to(&Location::new(-43.9, 151.2, "".into())) - 180.0).abs() < 1e-9);
+    }
 }
@@ -0,0 +1,299 @@
+use std::f64::consts::PI;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::tidal_data_record::TidalDataRecordCollection,
+    spectra::Spectra,
+    swell::Swell,
+    tools::waves::{iribarren_number, Error},
+    units::Unit,
+};
+
+const GRAVITY: f64 = 9.81;
+
+/// Empirical formulations for the wave runup on a beach
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub enum RunupModel {
+    /// Stockdon et al. (2006), separating setup and swash
+    #[default]
+    Stockdon,
+    /// Holman (1986), R2 = H0 * (0.83 * xi0 + 0.2)
+    Holman,
+    /// Nielsen & Hanslow (1991), R2 = 1.98 * Lz with the vertical runup scale Lz depending on
+    /// whether the beach is steeper than 1:10. Lz is scaled by the deep water root mean square
+    /// wave height H0rms = H0 / sqrt(2)
+    NielsenHanslow,
+}
+
+/// Wave runup on a beach, all heights are in meters above the still water level
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Runup {
+    /// Height exceeded by 2% of the runup maxima
+    pub r2: f64,
+    /// Time averaged wave setup at the shoreline, only separated by the Stockdon model
+    pub setup: Option<f64>,
+    /// Significant swash height, only separated by the Stockdon model
+    pub swash: Option<f64>,
+}
+
+impl RunupModel {
+    /// Estimates the runup for the given deep water significant wave height in meters, peak
+    /// period in seconds and foreshore beach slope. Models formulated with a different wave
+    /// height, like the root mean square height of Nielsen & Hanslow, convert it internally
+    pub fn runup(&self, wave_height: f64, period: f64, beach_slope: f64) -> Result<Runup, Error> {
+        if wave_height <= 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "wave height must be positive, got {wave_height}"
+            )));
+        }
+        if period <= 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "period must be positive, got {period}"
+            )));
+        }
+        if beach_slope <= 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "beach slope must be positive, got {beach_slope}"
+            )));
+        }
+
+        let deep_wavelength = (GRAVITY * period.powi(2)) / (2.0 * PI);
+        let scale = (wave_height * deep_wavelength).sqrt();
+        let iribarren = iribarren_number(beach_slope, wave_height, period);
+
+        let runup = match self {
+            RunupModel::Stockdon => {
+                if iribarren < 0.3 {
+                    // Dissipative beaches are dominated by infragravity swash
+                    Runup {
+                        r2: 0.043 * scale,
+                        setup: Some(0.016 * scale),
+                        swash: Some(0.046 * scale),
+                    }
+                } else {
+                    let setup = 0.35 * beach_slope * scale;
+                    let incident = 0.75 * beach_slope * scale;
+                    let infragravity = 0.06 * scale;
+                    let swash = incident.hypot(infragravity);
+                    Runup {
+                        r2: 1.1 * (setup + 0.5 * swash),
+                        setup: Some(setup),
+                        swash: Some(swash),
+                    }
+                }
+            }
+            RunupModel::Holman => Runup {
+                r2: wave_height * (0.83 * iribarren + 0.2),
+                setup: None,
+                swash: None,
+            },
+            RunupModel::NielsenHanslow => {
+                let scale = (wave_height / 2f64.sqrt() * deep_wavelength).sqrt();
+                let lz = if beach_slope >= 0.1 {
+                    0.6 * beach_slope * scale
+                } else {
+                    0.05 * scale
+                };
+                Runup {
+                    r2: 1.98 * lz,
+                    setup: None,
+                    swash: None,
+                }
+            }
+        };
+
+        Ok(runup)
+    }
+
+    /// Estimates the runup of a swell component on a beach with the given foreshore slope
+    pub fn swell_runup(&self, swell: &Swell, beach_slope: f64) -> Result<Runup, Error> {
+        let wave_height = swell
+            .wave_height
+            .value
+            .ok_or_else(|| Error::MissingData("swell wave height".to_string()))?;
+        let period = swell
+            .period
+            .value
+            .ok_or_else(|| Error::MissingData("swell period".to_string()))?;
+
+        let wave_height = swell.wave_height.unit.convert(wave_height, &Unit::Meters);
+        self.runup(wave_height, period, beach_slope)
+    }
+
+    /// Estimates the runup from the significant wave height and peak period of a spectra on a
+    /// beach with the given foreshore slope
+    pub fn spectra_runup(&self, spectra: &Spectra, beach_slope: f64) -> Result<Runup, Error> {
+        let wave_height = spectra
+            .hm0(None)
+            .value
+            .ok_or_else(|| Error::MissingData("spectral wave height".to_string()))?;
+        let period = spectra
+            .peak_period(None)
+            .value
+            .ok_or_else(|| Error::MissingData("spectral peak period".to_string()))?;
+
+        self.runup(wave_height, period, beach_slope)
+    }
+}
+
+/// Still water level from the tide combined with the wave runup, all levels are in meters
+/// relative to the tidal datum
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TotalWaterLevel {
+    pub date: DateTime<Utc>,
+    pub tide: f64,
+    /// 2% runup above the still water level
+    pub runup: f64,
+    pub total: f64,
+}
+
+/// Combines tide predictions, with values in the given unit, with a time series of runup
+/// estimates into a total water level series in meters. Runup is linearly interpolated to the
+/// tide times, and tide times outside of the runup series are dropped
+pub fn total_water_level(
+    tides: &TidalDataRecordCollection,
+    tide_unit: &Unit,
+    runup: &[(DateTime<Utc>, Runup)],
+) -> Vec<TotalWaterLevel> {
+    tides
+        .records
+        .iter()
+        .filter_map(|record| {
+            let r2 = interpolate_runup(runup, &record.date)?;
+            let tide = tide_unit.convert(record.value, &Unit::Meters);
+            Some(TotalWaterLevel {
+                date: record.date,
+                tide,
+                runup: r2,
+                total: tide + r2,
+            })
+        })
+        .collect()
+}
+
+/// Linearly interpolates R2 in time, runup is expected to be sorted by date
+fn interpolate_runup(runup: &[(DateTime<Utc>, Runup)], date: &DateTime<Utc>) -> Option<f64> {
+    let after = runup.iter().position(|(d, _)| d >= date)?;
+    let (end_date, end) = &runup[after];
+    if end_date == date {
+        return Some(end.r2);
+    }
+    if after == 0 {
+        return None;
+    }
+
+    let (start_date, start) = &runup[after - 1];
+    let span = (*end_date - *start_date).num_seconds() as f64;
+    let fraction = (*date - *start_date).num_seconds() as f64 / span;
+    Some(start.r2 + (end.r2 - start.r2) * fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{total_water_level, Runup, RunupModel};
+    use crate::{
+        data::tidal_data_record::{TidalDataRecord, TidalDataRecordCollection},
+        swell::Swell,
+        tools::waves::Error,
+        units::{Direction, Unit, UnitSystem},
+    };
+
+    #[test]
+    fn test_runup_models() {
+        // 2 m 12 s waves on a 1:20 intermediate beach
+        let stockdon = RunupModel::Stockdon.runup(2.0, 12.0, 0.05).unwrap();
+        assert!((stockdon.r2 - 1.233).abs() < 0.005);
+        assert!(stockdon.setup.unwrap() < stockdon.r2);
+        assert!(stockdon.swash.unwrap() > 0.0);
+
+        // Dissipative beaches use the infragravity only form
+        let dissipative = RunupModel::Stockdon.runup(2.0, 12.0, 0.005).unwrap();
+        assert!(
+            (dissipative.r2
+                - 0.043 * (2.0f64 * 9.81 * 144.0 / (2.0 * std::f64::consts::PI)).sqrt())
+            .abs()
+                < 1e-9
+        );
+
+        let holman = RunupModel::Holman.runup(2.0, 12.0, 0.05).unwrap();
+        let nielsen = RunupModel::NielsenHanslow.runup(2.0, 12.0, 0.05).unwrap();
+        assert!(holman.setup.is_none() && nielsen.swash.is_none());
+        let hrms_scale =
+            (2.0f64 / 2.0f64.sqrt() * 9.81 * 144.0 / (2.0 * std::f64::consts::PI)).sqrt();
+        assert!((nielsen.r2 - 1.98 * 0.05 * hrms_scale).abs() < 1e-9);
+        for r2 in [holman.r2, nielsen.r2] {
+            assert!(r2 > 0.5 * stockdon.r2 && r2 < 2.0 * stockdon.r2);
+        }
+
+        assert!(matches!(
+            RunupModel::Stockdon.runup(2.0, 12.0, 0.0),
+            Err(Error::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_swell_runup_units() {
+        let metric = Swell::new(
+            &UnitSystem::Metric,
+            2.0,
+            12.0,
+            Direction::from_degrees(90),
+            None,
+            None,
+            None,
+        );
+        let mut english = metric.clone();
+        english.wave_height.value = Some(2.0 * 3.281);
+        english.wave_height.unit = Unit::Feet;
+
+        let a = RunupModel::Stockdon.swell_runup(&metric, 0.05).unwrap();
+        let b = RunupModel::Stockdon.swell_runup(&english, 0.05).unwrap();
+        assert!((a.r2 - b.r2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_total_water_level() {
+        let start = Utc.with_ymd_and_hms(2023, 1, 2, 0, 0, 0).unwrap();
+        let tides = TidalDataRecordCollection {
+            records: (0..8)
+                .map(|i| TidalDataRecord {
+                    date: start + Duration::hours(i),
+                    value: i as f64 * 0.5,
+                    event: None,
+                })
+                .collect(),
+        };
+
+        let runup = vec![
+            (
+                start + Duration::hours(1),
+                Runup {
+                    r2: 1.0,
+                    setup: None,
+                    swash: None,
+                },
+            ),
+            (
+                start + Duration::hours(5),
+                Runup {
+                    r2: 2.0,
+                    setup: None,
+                    swash: None,
+                },
+            ),
+        ];
+
+        let levels = total_water_level(&tides, &Unit::Feet, &runup);
+        assert_eq!(levels.len(), 5);
+        assert_eq!(levels[0].date, start + Duration::hours(1));
+        assert!((levels[0].runup - 1.0).abs() < 1e-9);
+        assert!((levels[2].runup - 1.5).abs() < 1e-9);
+        assert!((levels[4].runup - 2.0).abs() < 1e-9);
+        assert!((levels[2].tide - 1.5 / 3.281).abs() < 1e-9);
+        assert!((levels[2].total - levels[2].tide - levels[2].runup).abs() < 1e-9);
+    }
+}
@@ -1,18 +1,34 @@
+use std::fmt;
+
 use geojson::{FeatureCollection, GeoJson};
 use kdtree::{distance::squared_euclidean, KdTree};
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    swell::{SwellProviderError, SwellSummary},
+    dimensional_data::DimensionalData,
+    swell::{Swell, SwellProviderError, SwellSummary},
     tools::{
         analysis::{bilerp, lerp, watershed, WatershedError},
         contour::{compute_contours, ContourError},
         interpolation::{circular_pchip_interpolate, PchipInterpolator},
         linspace::linspace,
-        vector::diff,
-        waves::pt_mean,
+        math::f_eq,
+        parametric::{
+            directional_distribution, frequency_spectrum, DirectionalSpreading, SpectralShape,
+        },
+        partition::{partition_spectrum, PartitionConfig},
+        simulation::random_phase_surface,
+        statistics::{
+            expected_max_wave_height, goda_peakedness, rayleigh_h1_10, rayleigh_h1_3,
+            spectral_bandwidth, spectral_narrowness, wave_group_length,
+        },
+        vector::{argsort_partial, diff},
+        waves::{
+            self, celerity, group_velocity, is_wind_sea, ldis, pt_mean, refraction_coefficient,
+            shoaling_coefficient, wind_sea_separation_frequency,
+        },
     },
-    units::direction::DirectionConvention,
+    units::{direction::DirectionConvention, Unit, UnitConvertible, UnitSystem},
 };
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -21,6 +37,25 @@ pub enum SpectralAxis {
     Direction,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SpectraError {
+    InvalidGrid(String),
+    DirectionConventionMismatch,
+    InvalidTimeSeries(String),
+}
+
+impl fmt::Display for SpectraError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpectraError::InvalidGrid(s) => write!(f, "invalid spectral grid: {s}"),
+            SpectraError::DirectionConventionMismatch => {
+                write!(f, "direction conventions differ and cannot be reconciled")
+            }
+            SpectraError::InvalidTimeSeries(s) => write!(f, "invalid spectra time series: {s}"),
+        }
+    }
+}
+
 /// Pre-computed mapping from cartesian pixel indices to spectral indices.
 /// Used to accelerate repeated calls to `project_cartesian_with_map` when
 /// the frequency/direction grid remains constant across multiple spectra.
@@ -32,6 +67,19 @@ pub struct CartesianProjectionMap {
     pub indices: Vec<Option<usize>>,
 }
 
+/// Wraps an integrated spectral parameter with its name and unit
+pub(crate) fn bulk_data(
+    value: Option<f64>,
+    variable_name: &str,
+    unit: Unit,
+) -> DimensionalData<f64> {
+    DimensionalData {
+        value,
+        variable_name: variable_name.into(),
+        unit,
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Spectra {
     /// Frequency bins in hz
@@ -96,6 +144,119 @@ impl Spectra {
             .collect()
     }
 
+    /// Synthesize a spectra on the given frequency (hz) and direction (degrees) grid from bulk
+    /// parameters, with the peak direction in degrees. The energy is scaled so the integrated
+    /// Hm0 matches hs. Always outputs DirectionConvention::From
+    pub fn from_parametric(
+        frequency: &[f64],
+        direction: &[f64],
+        hs: f64,
+        tp: f64,
+        peak_direction: f64,
+        shape: &SpectralShape,
+        spreading: &DirectionalSpreading,
+    ) -> Spectra {
+        let direction_rad = direction
+            .iter()
+            .map(|d| d.to_radians())
+            .collect::<Vec<f64>>();
+
+        let ef = frequency_spectrum(shape, hs, tp, frequency);
+        let dist =
+            directional_distribution(spreading, peak_direction, direction, &diff(&direction_rad));
+
+        let energy = dist
+            .iter()
+            .flat_map(|d| ef.iter().map(move |e| e * d))
+            .collect();
+
+        Spectra::new(
+            frequency.to_vec(),
+            direction_rad,
+            energy,
+            DirectionConvention::From,
+        )
+    }
+
+    /// Superpose parametric spectra for each swell component on the given frequency (hz) and
+    /// direction (degrees) grid. Each component keeps its own Hm0, peak period and peak direction,
+    /// components missing any of these are skipped. Always outputs DirectionConvention::From
+    pub fn from_parametric_components(
+        frequency: &[f64],
+        direction: &[f64],
+        components: &[(Swell, SpectralShape, DirectionalSpreading)],
+    ) -> Spectra {
+        let mut energy = vec![0.0; frequency.len() * direction.len()];
+
+        for (swell, shape, spreading) in components {
+            let mut swell = swell.clone();
+            swell.to_units(&UnitSystem::Metric);
+
+            let (Some(hs), Some(tp), Some(dir)) = (
+                swell.wave_height.get_value_opt(),
+                swell.period.get_value_opt(),
+                swell.direction.get_value_opt(),
+            ) else {
+                continue;
+            };
+
+            if hs <= 0.0 || tp <= 0.0 {
+                continue;
+            }
+
+            let component = Spectra::from_parametric(
+                frequency,
+                direction,
+                hs,
+                tp,
+                dir.degrees as f64,
+                shape,
+                spreading,
+            );
+
+            energy
+                .iter_mut()
+                .zip(component.energy.iter())
+                .for_each(|(e, c)| *e += c);
+        }
+
+        Spectra::new(
+            frequency.to_vec(),
+            direction.iter().map(|d| d.to_radians()).collect(),
+            energy,
+            DirectionConvention::From,
+        )
+    }
+
+    /// Converts the spectra to the given direction convention, rotating the raw direction bins
+    /// and sorting them in ascending order along with the energy
+    pub fn convert_convention(&self, dir_convention: DirectionConvention) -> Spectra {
+        let nk = self.nk();
+
+        let direction = self
+            .direction_deg()
+            .iter()
+            .map(|d| (dir_convention.denormalize(*d) + 360.0) % 360.0)
+            .collect::<Vec<f64>>();
+        let sort_indices = argsort_partial(&direction);
+
+        let mut energy = vec![0.0; self.energy.len()];
+        for (new_ith, old_ith) in sort_indices.iter().enumerate() {
+            energy[new_ith * nk..(new_ith + 1) * nk]
+                .copy_from_slice(&self.energy[old_ith * nk..(old_ith + 1) * nk]);
+        }
+
+        Spectra::new(
+            self.frequency.clone(),
+            sort_indices
+                .iter()
+                .map(|i| direction[*i].to_radians())
+                .collect(),
+            energy,
+            dir_convention,
+        )
+    }
+
     /// Period bins
     pub fn period(&self) -> Vec<f64> {
         self.frequency.iter().map(|f| 1.0 / f).collect()
@@ -332,6 +493,587 @@ impl Spectra {
             .collect()
     }
 
+    /// Mask of the frequency bins that fall within the given band, inclusive on both ends.
+    /// When no band is given every bin is included
+    fn band_mask(&self, band: Option<(f64, f64)>) -> Vec<bool> {
+        self.frequency
+            .iter()
+            .map(|f| match band {
+                Some((low, high)) => *f >= low && *f <= high,
+                None => true,
+            })
+            .collect()
+    }
+
+    /// Integrated frequency moment i of the full spectra, optionally limited to a frequency band
+    fn band_moment(&self, mom_i: i32, band: Option<(f64, f64)>) -> f64 {
+        let oned = self.oned(SpectralAxis::Frequency);
+        let dk = self.dk();
+
+        self.band_mask(band)
+            .iter()
+            .enumerate()
+            .filter(|(_, in_band)| **in_band)
+            .map(|(ik, _)| self.frequency[ik].powi(mom_i) * oned[ik] * dk[ik])
+            .sum()
+    }
+
+    /// Integrated cosine and sine weighted energy in the form of (ecos, esin) using the raw
+    /// direction bins, optionally limited to a frequency band
+    fn band_directional_moment(&self, band: Option<(f64, f64)>) -> (f64, f64) {
+        let dk = self.dk();
+        let dth = self.dth();
+        let mask = self.band_mask(band);
+
+        let mut ecos = 0.0;
+        let mut esin = 0.0;
+        for (ik, in_band) in mask.iter().enumerate() {
+            if !in_band {
+                continue;
+            }
+
+            for (ith, (d, dd)) in self.direction.iter().zip(dth.iter()).enumerate() {
+                let e = self.energy_at(ik, ith) * dd * dk[ik];
+                ecos += e * d.cos();
+                esin += e * d.sin();
+            }
+        }
+
+        (ecos, esin)
+    }
+
+    /// Index of the frequency bin with the most energy within the given band
+    fn band_peak_index(&self, band: Option<(f64, f64)>) -> Option<usize> {
+        let oned = self.oned(SpectralAxis::Frequency);
+
+        self.band_mask(band)
+            .iter()
+            .enumerate()
+            .filter(|(ik, in_band)| **in_band && oned[*ik] > 0.0)
+            .max_by(|(a, _), (b, _)| oned[*a].partial_cmp(&oned[*b]).unwrap())
+            .map(|(ik, _)| ik)
+    }
+
+    /// Converts a raw direction in radians to degrees in the DirectionConvention::From convention
+    fn normalized_direction(&self, raw: f64) -> f64 {
+        let raw = (raw.to_degrees() + 360.0) % 360.0;
+        self.dir_convention.normalize(raw)
+    }
+
+    /// Significant wave height Hm0 = 4 * sqrt(m0) in meters, optionally limited to a frequency band
+    pub fn hm0(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let m0 = self.band_moment(0, band);
+        let value = if m0 > 0.0 {
+            Some(4.0 * m0.sqrt())
+        } else {
+            None
+        };
+        bulk_data(value, "significant wave height", Unit::Meters)
+    }
+
+    /// Frequency of the most energetic bin of the one dimensional spectra in hz,
+    /// optionally limited to a frequency band
+    pub fn peak_frequency(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let value = self.band_peak_index(band).map(|ik| self.frequency[ik]);
+        bulk_data(value, "peak frequency", Unit::Hertz)
+    }
+
+    /// Peak frequency in hz refined with a parabolic fit through the most energetic bin and its
+    /// neighbors, optionally limited to a frequency band. Falls back to the discrete peak
+    /// frequency when the peak sits on the edge of the band.
+    pub fn peak_frequency_parabolic(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let oned = self.oned(SpectralAxis::Frequency);
+        let mask = self.band_mask(band);
+
+        let value = self.band_peak_index(band).map(|ik| {
+            if ik == 0 || ik == self.nk() - 1 || !mask[ik - 1] || !mask[ik + 1] {
+                return self.frequency[ik];
+            }
+
+            let (f0, f1, f2) = (
+                self.frequency[ik - 1],
+                self.frequency[ik],
+                self.frequency[ik + 1],
+            );
+            let (e0, e1, e2) = (oned[ik - 1], oned[ik], oned[ik + 1]);
+
+            let numer = (f1 - f0).powi(2) * (e1 - e2) - (f1 - f2).powi(2) * (e1 - e0);
+            let denom = (f1 - f0) * (e1 - e2) - (f1 - f2) * (e1 - e0);
+            if denom == 0.0 {
+                return f1;
+            }
+
+            (f1 - 0.5 * numer / denom).clamp(f0, f2)
+        });
+
+        bulk_data(value, "peak frequency", Unit::Hertz)
+    }
+
+    /// Period of the most energetic bin of the one dimensional spectra in seconds,
+    /// optionally limited to a frequency band
+    pub fn peak_period(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let value = self.peak_frequency(band).value.map(|f| 1.0 / f);
+        bulk_data(value, "peak period", Unit::Seconds)
+    }
+
+    /// Peak period in seconds refined with a parabolic fit around the spectral peak,
+    /// optionally limited to a frequency band
+    pub fn peak_period_parabolic(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let value = self.peak_frequency_parabolic(band).value.map(|f| 1.0 / f);
+        bulk_data(value, "peak period", Unit::Seconds)
+    }
+
+    /// Mean wave period Tm01 = m0 / m1 in seconds, optionally limited to a frequency band
+    pub fn tm01(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let m0 = self.band_moment(0, band);
+        let m1 = self.band_moment(1, band);
+        let value = if m0 > 0.0 && m1 > 0.0 {
+            Some(m0 / m1)
+        } else {
+            None
+        };
+        bulk_data(value, "mean wave period", Unit::Seconds)
+    }
+
+    /// Zero upcrossing period Tm02 = sqrt(m0 / m2) in seconds, optionally limited to a frequency band
+    pub fn tm02(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let m0 = self.band_moment(0, band);
+        let m2 = self.band_moment(2, band);
+        let value = if m0 > 0.0 && m2 > 0.0 {
+            Some((m0 / m2).sqrt())
+        } else {
+            None
+        };
+        bulk_data(value, "zero upcrossing period", Unit::Seconds)
+    }
+
+    /// Energy period Tm-10 = m-1 / m0 in seconds, optionally limited to a frequency band
+    pub fn energy_period(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let m0 = self.band_moment(0, band);
+        let m_1 = self.band_moment(-1, band);
+        let value = if m0 > 0.0 { Some(m_1 / m0) } else { None };
+        bulk_data(value, "energy period", Unit::Seconds)
+    }
+
+    /// Mean wave direction of the peak frequency bin in degrees using the
+    /// DirectionConvention::From convention, optionally limited to a frequency band
+    pub fn peak_direction(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let value = self.band_peak_index(band).map(|ik| {
+            let dth = self.dth();
+            let (ecos, esin) = (0..self.nth()).fold((0.0, 0.0), |(ecos, esin), ith| {
+                let e = self.energy_at(ik, ith) * dth[ith];
+                (
+                    ecos + e * self.direction[ith].cos(),
+                    esin + e * self.direction[ith].sin(),
+                )
+            });
+            self.normalized_direction(esin.atan2(ecos))
+        });
+
+        bulk_data(value, "peak wave direction", Unit::Degrees)
+    }
+
+    /// Mean wave direction of the full two dimensional energy in degrees using the
+    /// DirectionConvention::From convention, optionally limited to a frequency band
+    pub fn mean_direction(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let m0 = self.band_moment(0, band);
+        let (ecos, esin) = self.band_directional_moment(band);
+        let value = if m0 > 0.0 {
+            Some(self.normalized_direction(esin.atan2(ecos)))
+        } else {
+            None
+        };
+        bulk_data(value, "mean wave direction", Unit::Degrees)
+    }
+
+    /// Directional spread (Kuik et al, 1988) of the full two dimensional energy in degrees,
+    /// optionally limited to a frequency band
+    pub fn directional_spread(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let m0 = self.band_moment(0, band);
+        let (ecos, esin) = self.band_directional_moment(band);
+        let value = if m0 > 0.0 {
+            let m1 = (ecos.powi(2) + esin.powi(2)).sqrt() / m0;
+            Some((2.0 * (1.0 - m1)).max(0.0).sqrt().to_degrees())
+        } else {
+            None
+        };
+        bulk_data(value, "directional spread", Unit::Degrees)
+    }
+
+    /// Depth averaged wave energy density rho * g * m0 in J/m2, optionally limited to a
+    /// frequency band
+    pub fn energy_density(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let m0 = self.band_moment(0, band);
+        let value = if m0 > 0.0 {
+            Some(UnitSystem::Metric.density_of_seawater() * 9.81 * m0)
+        } else {
+            None
+        };
+        bulk_data(value, "energy density", Unit::JoulesPerSquareMeter)
+    }
+
+    /// Wave power in kW/m, integrating the group velocity times the energy over every
+    /// frequency and direction bin, optionally limited to a frequency band. When depth in meters
+    /// is not supplied use deep water approximation
+    pub fn wave_power(&self, depth: Option<f64>, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let oned = self.oned(SpectralAxis::Frequency);
+        let dk = self.dk();
+
+        let flux = self
+            .band_mask(band)
+            .iter()
+            .enumerate()
+            .filter(|(ik, in_band)| **in_band && self.frequency[*ik] > 0.0)
+            .map(|(ik, _)| group_velocity(self.frequency[ik], depth) * oned[ik] * dk[ik])
+            .sum::<f64>();
+
+        let value = if flux > 0.0 {
+            Some(UnitSystem::Metric.density_of_seawater() * 9.81 * flux / 1000.0)
+        } else {
+            None
+        };
+        bulk_data(value, "wave power", Unit::KiloWattsPerMeter)
+    }
+
+    /// One dimensional frequency spectrum in m2/hz with the energy outside of the band zeroed
+    fn band_oned(&self, band: Option<(f64, f64)>) -> Vec<f64> {
+        self.oned(SpectralAxis::Frequency)
+            .iter()
+            .zip(self.band_mask(band).iter())
+            .map(|(e, in_band)| if *in_band { *e } else { 0.0 })
+            .collect()
+    }
+
+    /// Goda peakedness parameter Qp, optionally limited to a frequency band
+    pub fn goda_peakedness(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let value = goda_peakedness(&self.frequency, &self.band_oned(band), &self.dk());
+        bulk_data(value, "peakedness", Unit::Dimensionless)
+    }
+
+    /// Longuet-Higgins spectral bandwidth nu, optionally limited to a frequency band
+    pub fn spectral_bandwidth(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let value = spectral_bandwidth(&self.frequency, &self.band_oned(band), &self.dk());
+        bulk_data(value, "spectral bandwidth", Unit::Dimensionless)
+    }
+
+    /// Spectral narrowness epsilon, optionally limited to a frequency band
+    pub fn spectral_narrowness(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let value = spectral_narrowness(&self.frequency, &self.band_oned(band), &self.dk());
+        bulk_data(value, "spectral narrowness", Unit::Dimensionless)
+    }
+
+    /// Expected number of waves higher than the significant wave height in a group, optionally
+    /// limited to a frequency band
+    pub fn wave_group_length(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let value = wave_group_length(&self.frequency, &self.band_oned(band), &self.dk());
+        bulk_data(value, "wave group length", Unit::Dimensionless)
+    }
+
+    /// Rayleigh based mean height of the highest third of the waves in meters, optionally
+    /// limited to a frequency band
+    pub fn h1_3(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let m0 = self.band_moment(0, band);
+        let value = if m0 > 0.0 {
+            Some(rayleigh_h1_3(m0))
+        } else {
+            None
+        };
+        bulk_data(value, "significant wave height", Unit::Meters)
+    }
+
+    /// Rayleigh based mean height of the highest tenth of the waves in meters, optionally
+    /// limited to a frequency band
+    pub fn h1_10(&self, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let m0 = self.band_moment(0, band);
+        let value = if m0 > 0.0 {
+            Some(rayleigh_h1_10(m0))
+        } else {
+            None
+        };
+        bulk_data(value, "highest tenth wave height", Unit::Meters)
+    }
+
+    /// Rayleigh based expected maximum wave height in meters for a storm lasting the given
+    /// duration in seconds, optionally limited to a frequency band
+    pub fn hmax(&self, duration: f64, band: Option<(f64, f64)>) -> DimensionalData<f64> {
+        let m0 = self.band_moment(0, band);
+        let value = self
+            .tm02(band)
+            .value
+            .and_then(|tz| expected_max_wave_height(m0, tz, duration));
+        bulk_data(value, "maximum wave height", Unit::Meters)
+    }
+
+    /// Synthesises a random phase sea surface elevation time series in meters at a point,
+    /// sampled every dt seconds over the given duration in seconds. Passing a seed gives a
+    /// reproducible series
+    pub fn surface_elevation(&self, duration: f64, dt: f64, seed: Option<u64>) -> Vec<f64> {
+        random_phase_surface(
+            &self.frequency,
+            &self.oned(SpectralAxis::Frequency),
+            &self.dk(),
+            duration,
+            dt,
+            seed,
+        )
+    }
+
+    /// Transforms the spectra to the given depth in meters over straight and parallel bottom
+    /// contours, applying the shoaling and refraction coefficients to every frequency and
+    /// direction bin. The shore normal is the direction in degrees (DirectionConvention::From)
+    /// that waves travelling straight onshore come from. Energy travelling away from shore is
+    /// dropped and refracted energy is moved to the closest direction bin. Always outputs
+    /// DirectionConvention::From
+    pub fn transform_to_depth(
+        &self,
+        depth: f64,
+        shore_normal: f64,
+    ) -> Result<Spectra, waves::Error> {
+        // Met and Towards bins are descending or wrap through zero once normalized, so work on a
+        // monotone From grid to keep the bin widths positive
+        let source = self.convert_convention(DirectionConvention::From);
+        let nk = source.nk();
+        let direction = source.direction_deg();
+        let dth = source.dth();
+
+        let mut energy = vec![0.0; source.energy.len()];
+
+        for (ik, freq) in source.frequency.iter().enumerate() {
+            if *freq <= 0.0 {
+                continue;
+            }
+
+            let wavelength = ldis(1.0 / freq, depth)?;
+            let ks = shoaling_coefficient(wavelength, depth);
+
+            for (ith, dir) in direction.iter().enumerate() {
+                let e = source.energy_at(ik, ith);
+                if e <= 0.0 {
+                    continue;
+                }
+
+                let incident_angle = (dir - shore_normal + 540.0) % 360.0 - 180.0;
+                if incident_angle.abs() >= 90.0 {
+                    continue;
+                }
+
+                let (kr, shallow_angle) = refraction_coefficient(wavelength, depth, incident_angle);
+                let shallow_dir = (shore_normal + shallow_angle + 360.0) % 360.0;
+
+                let (target, _) = direction
+                    .iter()
+                    .enumerate()
+                    .map(|(i, d)| (i, 180.0 - ((d - shallow_dir).abs() - 180.0).abs()))
+                    .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                    .unwrap();
+
+                energy[ik + target * nk] += e * (ks * kr).powi(2) * dth[ith] / dth[target];
+            }
+        }
+
+        Ok(Spectra::new(
+            source.frequency.clone(),
+            direction.iter().map(|d| d.to_radians()).collect(),
+            energy,
+            DirectionConvention::From,
+        ))
+    }
+
+    /// Whether the given spectra shares the exact same frequency and direction bins
+    /// and direction convention
+    fn same_grid(&self, other: &Spectra) -> bool {
+        self.dir_convention == other.dir_convention
+            && self.frequency == other.frequency
+            && self.nth() == other.nth()
+            && self
+                .direction
+                .iter()
+                .zip(other.direction.iter())
+                .all(|(a, b)| f_eq(*a, *b))
+    }
+
+    /// Energy of the other spectra on this spectra's frequency and direction bins, interpolating
+    /// when the grids differ. Bins outside of the other spectra's frequency range are zero
+    fn aligned_energy(&self, other: &Spectra) -> Result<Vec<f64>, SpectraError> {
+        for spectra in [self, other] {
+            if spectra.energy.len() != spectra.nk() * spectra.nth() || spectra.nk() < 2 {
+                return Err(SpectraError::InvalidGrid(
+                    "energy does not match the frequency and direction bins".into(),
+                ));
+            }
+        }
+
+        if self.same_grid(other) {
+            return Ok(other.energy.clone());
+        }
+
+        // Prefer an exact rotation over interpolating when only the conventions differ
+        if self.dir_convention != other.dir_convention && other.nth() > 1 {
+            let converted = other.convert_convention(self.dir_convention.clone());
+            if self.same_grid(&converted) {
+                return Ok(converted.energy);
+            }
+        }
+
+        // A single direction bin carries no directional information to rotate into another convention
+        if other.nth() < 2 {
+            if self.dir_convention != other.dir_convention {
+                return Err(SpectraError::DirectionConventionMismatch);
+            }
+
+            return Err(SpectraError::InvalidGrid(
+                "at least two direction bins are needed to interpolate".into(),
+            ));
+        }
+
+        let (self_min, self_max) = (self.frequency[0], self.frequency[self.nk() - 1]);
+        let (other_min, other_max) = (other.frequency[0], other.frequency[other.nk() - 1]);
+        if other_min > self_max || other_max < self_min {
+            return Err(SpectraError::InvalidGrid(
+                "frequency ranges do not overlap".into(),
+            ));
+        }
+
+        // The interpolated direction bins follow this spectra's direction bins one to one
+        let mut energy = other
+            .interpolate_to_grid(&self.frequency, &self.direction_deg())
+            .energy;
+
+        // Interpolation clamps above the highest frequency of the other spectra, which would
+        // copy its last bin into every higher bin, so those bins carry no energy instead
+        let nk = self.nk();
+        energy
+            .iter_mut()
+            .enumerate()
+            .filter(|(i, _)| self.frequency[i % nk] > other_max)
+            .for_each(|(_, e)| *e = 0.0);
+
+        Ok(energy)
+    }
+
+    /// This spectra on the frequency and direction bins and direction convention of the target
+    /// spectra, interpolating when the grids differ
+    pub fn align_to(&self, target: &Spectra) -> Result<Spectra, SpectraError> {
+        let energy = target.aligned_energy(self)?;
+
+        Ok(Spectra::new(
+            target.frequency.clone(),
+            target.direction.clone(),
+            energy,
+            target.dir_convention.clone(),
+        ))
+    }
+
+    /// Adds the energy of the other spectra to this spectra. When the grids differ the other
+    /// spectra is interpolated onto this spectra's frequency and direction bins first. The result
+    /// keeps this spectra's grid and direction convention
+    pub fn add(&self, other: &Spectra) -> Result<Spectra, SpectraError> {
+        let other_energy = self.aligned_energy(other)?;
+
+        Ok(Spectra::new(
+            self.frequency.clone(),
+            self.direction.clone(),
+            self.energy
+                .iter()
+                .zip(other_energy.iter())
+                .map(|(a, b)| a + b)
+                .collect(),
+            self.dir_convention.clone(),
+        ))
+    }
+
+    /// Subtracts the energy of the other spectra from this spectra. When the grids differ the other
+    /// spectra is interpolated onto this spectra's frequency and direction bins first. The result
+    /// keeps this spectra's grid and direction convention. Negative energy is kept so the result
+    /// can be used as a difference spectra
+    pub fn subtract(&self, other: &Spectra) -> Result<Spectra, SpectraError> {
+        let other_energy = self.aligned_energy(other)?;
+
+        Ok(Spectra::new(
+            self.frequency.clone(),
+            self.direction.clone(),
+            self.energy
+                .iter()
+                .zip(other_energy.iter())
+                .map(|(a, b)| a - b)
+                .collect(),
+            self.dir_convention.clone(),
+        ))
+    }
+
+    /// Scales the energy of every bin by the given factor
+    pub fn scale(&self, factor: f64) -> Spectra {
+        Spectra::new(
+            self.frequency.clone(),
+            self.direction.clone(),
+            self.energy.iter().map(|e| e * factor).collect(),
+            self.dir_convention.clone(),
+        )
+    }
+
+    /// Mask of the energy bins that are forced by the wind under the wave age criterion,
+    /// (Hanson & Phillips, 2001). Wind speed is in m/s and wind direction is in degrees using
+    /// DirectionConvention::From. When depth is not supplied the deep water phase speed is used
+    pub fn wind_sea_mask(
+        &self,
+        wind_speed: f64,
+        wind_direction: f64,
+        depth: Option<f64>,
+    ) -> Vec<bool> {
+        let nk = self.nk();
+        let direction = self.direction_deg();
+        let phase_speed = self
+            .frequency
+            .iter()
+            .map(|f| celerity(*f, depth))
+            .collect::<Vec<f64>>();
+
+        (0..self.energy.len())
+            .map(|i| {
+                let (ik, ith) = (i % nk, i / nk);
+                is_wind_sea(phase_speed[ik], wind_speed, direction[ith] - wind_direction)
+            })
+            .collect()
+    }
+
+    /// Splits the spectra into wind sea and swell using the wave age criterion. Returns the
+    /// wind sea spectra, the swell spectra, and the separation frequency in hz for waves
+    /// travelling with the wind. Wind speed is in m/s and wind direction is in degrees using
+    /// DirectionConvention::From. The separation frequency follows
+    /// `wind_sea_separation_frequency`, it is None when there is no wind and the lowest searched
+    /// frequency when the wind outruns every wave in shallow water
+    pub fn separate_wind_sea(
+        &self,
+        wind_speed: f64,
+        wind_direction: f64,
+        depth: Option<f64>,
+    ) -> (Spectra, Spectra, Option<f64>) {
+        let mask = self.wind_sea_mask(wind_speed, wind_direction, depth);
+
+        let (wind_sea, swell): (Vec<f64>, Vec<f64>) = self
+            .energy
+            .iter()
+            .zip(mask.iter())
+            .map(|(e, is_wind_sea)| if *is_wind_sea { (*e, 0.0) } else { (0.0, *e) })
+            .unzip();
+
+        (
+            Spectra::new(
+                self.frequency.clone(),
+                self.direction.clone(),
+                wind_sea,
+                self.dir_convention.clone(),
+            ),
+            Spectra::new(
+                self.frequency.clone(),
+                self.direction.clone(),
+                swell,
+                self.dir_convention.clone(),
+            ),
+            wind_sea_separation_frequency(wind_speed, depth),
+        )
+    }
+
     /// The value range of the energy data in the form of (min, max)
     pub fn energy_range(&self) -> (f64, f64) {
         let min = self
@@ -347,7 +1089,8 @@ impl Spectra {
         (*min, *max)
     }
 
-    /// Partition the energy data into discrete swell components
+    /// Partition the energy data into discrete swell components. At most 255 levels are
+    /// supported, use `partition_with_config` for finer levels
     pub fn partition(
         &self,
         levels: usize,
@@ -362,6 +1105,33 @@ impl Spectra {
         )
     }
 
+    /// Partition the energy data into discrete swell components with the given config. Wind
+    /// speed is in m/s and wind direction is in degrees using DirectionConvention::From, both are
+    /// needed to separate wind sea with the PTM1, PTM2 and PTM4 methods. When wind sea is
+    /// separated it is always partition 1
+    pub fn partition_with_config(
+        &self,
+        config: &PartitionConfig,
+        depth: Option<f64>,
+        wind_speed: Option<f64>,
+        wind_direction: Option<f64>,
+    ) -> Result<(Vec<i32>, usize), WatershedError> {
+        let wind_sea_mask = match (wind_speed, wind_direction) {
+            (Some(speed), Some(direction)) => Some(self.wind_sea_mask(speed, direction, depth)),
+            _ => None,
+        };
+
+        partition_spectrum(
+            &self.energy,
+            &self.frequency,
+            &self.direction_deg(),
+            &self.dk(),
+            &self.dth(),
+            wind_sea_mask.as_deref(),
+            config,
+        )
+    }
+
     /// Extract swell components
     pub fn swell_data(
         &self,
@@ -503,6 +1273,25 @@ impl Spectra {
         self.project_cartesian_with_map(target, &map)
     }
 
+    /// Maps a contour point in fractional frequency and direction bin indices to period in
+    /// seconds and direction in degrees. Direction indices past the last bin fall between the
+    /// last bin and the first bin one full turn later, for grids padded with a wrapped column
+    fn contour_coordinates(&self, point: &[f64]) -> Vec<f64> {
+        let x = 1.0 / self.ik(point[0]);
+        let last = (self.nth() - 1) as f64;
+        let direction = if point[1] > last {
+            let lower = self.direction[self.nth() - 1];
+            let step = (self.direction[0] - lower + std::f64::consts::PI)
+                .rem_euclid(2.0 * std::f64::consts::PI)
+                - std::f64::consts::PI;
+            lower + (point[1] - last) * step
+        } else {
+            self.ith(point[1])
+        };
+        let y = self.dir_convention.normalize(direction.to_degrees());
+        vec![x, y]
+    }
+
     /// Contours
     pub fn contoured(&self) -> Result<GeoJson, ContourError> {
         let (_min, max) = self.energy_range();
@@ -513,13 +1302,7 @@ impl Spectra {
             self.nk(),
             self.nth(),
             &t,
-            Some(|point: &Vec<f64>| {
-                let x = 1.0 / self.ik(point[0]);
-                let y = self
-                    .dir_convention
-                    .normalize(self.ith(point[1]).to_degrees());
-                vec![x, y]
-            }),
+            Some(|point: &Vec<f64>| self.contour_coordinates(point)),
             None::<Box<dyn Fn(&usize, &f64) -> String>>,
         )?;
 
@@ -530,6 +1313,59 @@ impl Spectra {
         }))
     }
 
+    /// Outlines every partition of the given partition map with a polygon feature in the same
+    /// period and direction coordinates as `contoured`. Each feature carries the matching swell
+    /// component from the given summary as properties, partitions without a component are
+    /// skipped. The first direction bin is repeated after the last one so partitions crossing
+    /// the end of the direction grid are outlined up to the seam on both sides
+    pub fn partition_contours(
+        &self,
+        partitions: &(Vec<i32>, usize),
+        swell_data: &SwellSummary,
+    ) -> Result<GeoJson, ContourError> {
+        let (imo, _) = partitions;
+
+        let mut features = Vec::new();
+        for component in swell_data.components.iter() {
+            let Some(partition) = component.partition else {
+                continue;
+            };
+
+            let mut mask = imo
+                .iter()
+                .map(|p| if *p == partition as i32 { 1.0 } else { 0.0 })
+                .collect::<Vec<f64>>();
+            mask.extend_from_within(..self.nk());
+
+            let outlines = compute_contours(
+                &mask,
+                self.nk(),
+                self.nth() + 1,
+                &[0.5],
+                Some(|point: &Vec<f64>| self.contour_coordinates(point)),
+                None::<Box<dyn Fn(&usize, &f64) -> String>>,
+            )?;
+
+            for mut feature in outlines {
+                feature.set_property("partition", partition);
+                feature.set_property("wave_height", component.wave_height.get_value());
+                feature.set_property("period", component.period.get_value());
+                feature.set_property("direction", component.direction.get_value().degrees as f64);
+                if let Some(energy) = component.energy.as_ref() {
+                    feature.set_property("energy", energy.get_value());
+                }
+                feature.set_property("label", component.to_string());
+                features.push(feature);
+            }
+        }
+
+        Ok(GeoJson::from(FeatureCollection {
+            bbox: None,
+            features,
+            foreign_members: None,
+        }))
+    }
+
     /// Interpolate spectra to a new frequency/direction grid.
     ///
     /// Uses log-frequency PCHIP interpolation and circular direction
@@ -643,3 +1479,622 @@ impl Spectra {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use geojson::GeoJson;
+
+    use super::Spectra;
+    use crate::{
+        swell::Swell,
+        tools::{
+            analysis::WatershedError,
+            parametric::{DirectionalSpreading, SpectralShape},
+            zero_crossing::zero_crossing_analysis,
+        },
+        units::{direction::DirectionConvention, Direction, UnitConvertible, UnitSystem},
+    };
+
+    /// Gaussian frequency spectrum centered on fp with a cos2s spreading centered on dp
+    fn gaussian_spectra(hs: f64, fp: f64, dp: f64, dir_convention: DirectionConvention) -> Spectra {
+        let frequency = (0..200)
+            .map(|i| 0.03 + i as f64 * 0.0025)
+            .collect::<Vec<_>>();
+        let direction = (0..72)
+            .map(|i| (i as f64 * 5.0).to_radians())
+            .collect::<Vec<_>>();
+
+        let sigma = 0.01;
+        let m0 = (hs / 4.0).powi(2);
+        let dp = dp.to_radians();
+
+        let spreading = direction
+            .iter()
+            .map(|d| ((d - dp) / 2.0).cos().powi(20))
+            .collect::<Vec<_>>();
+        let spreading_sum = spreading.iter().sum::<f64>() * 5.0f64.to_radians();
+
+        let mut energy = vec![0.0; frequency.len() * direction.len()];
+        for (ith, s) in spreading.iter().enumerate() {
+            for (ik, f) in frequency.iter().enumerate() {
+                let ef =
+                    m0 / (sigma * (2.0 * PI).sqrt()) * (-0.5 * ((f - fp) / sigma).powi(2)).exp();
+                energy[ik + ith * frequency.len()] = ef * s / spreading_sum;
+            }
+        }
+
+        Spectra::new(frequency, direction, energy, dir_convention)
+    }
+
+    /// Geometric frequency grid from 0.035 hz and nth evenly spaced direction bins in degrees
+    fn parametric_grid(nth: usize) -> (Vec<f64>, Vec<f64>) {
+        let frequency = (0..40).map(|i| 0.035 * 1.07f64.powi(i)).collect::<Vec<_>>();
+        let direction = (0..nth)
+            .map(|i| i as f64 * 360.0 / nth as f64)
+            .collect::<Vec<_>>();
+        (frequency, direction)
+    }
+
+    /// JONSWAP spectrum with a cos2s spreading on the `parametric_grid`
+    fn parametric_spectra(hs: f64, tp: f64, dir: f64, nth: usize) -> Spectra {
+        let (frequency, direction) = parametric_grid(nth);
+        Spectra::from_parametric(
+            &frequency,
+            &direction,
+            hs,
+            tp,
+            dir,
+            &SpectralShape::Jonswap { gamma: 3.3 },
+            &DirectionalSpreading::CosineSquared { s: 20.0 },
+        )
+    }
+
+    #[test]
+    fn test_bulk_parameters() {
+        let spectra = gaussian_spectra(2.0, 0.08, 90.0, DirectionConvention::From);
+
+        let hm0 = spectra.hm0(None).value.unwrap();
+        assert!((hm0 - 2.0).abs() < 0.01);
+
+        let tp = spectra.peak_period(None).value.unwrap();
+        assert!((tp - 12.5).abs() < 0.01);
+
+        let tp_parabolic = spectra.peak_period_parabolic(None).value.unwrap();
+        assert!((tp_parabolic - 12.5).abs() < 0.01);
+
+        let tm01 = spectra.tm01(None).value.unwrap();
+        let tm02 = spectra.tm02(None).value.unwrap();
+        let tm_10 = spectra.energy_period(None).value.unwrap();
+        assert!(tm02 < tm01 && tm01 < tm_10);
+        assert!((tm01 - 12.5).abs() < 0.5);
+
+        let dp = spectra.peak_direction(None).value.unwrap();
+        let dm = spectra.mean_direction(None).value.unwrap();
+        assert!((dp - 90.0).abs() < 0.1);
+        assert!((dm - 90.0).abs() < 0.1);
+
+        // cos2s spreading with s = 10 gives a spread of sqrt(2 / (s + 1)), roughly 24.4 degrees
+        let spread = spectra.directional_spread(None).value.unwrap();
+        assert!((spread - 24.4).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_shape_statistics() {
+        let frequency = (0..200)
+            .map(|i| 0.03 + i as f64 * 0.0025)
+            .collect::<Vec<f64>>();
+        let direction = (0..36).map(|i| i as f64 * 10.0).collect::<Vec<f64>>();
+        let spreading = DirectionalSpreading::CosineSquared { s: 10.0 };
+        let broad = Spectra::from_parametric(
+            &frequency,
+            &direction,
+            2.0,
+            10.0,
+            90.0,
+            &SpectralShape::PiersonMoskowitz,
+            &spreading,
+        );
+        let narrow = Spectra::from_parametric(
+            &frequency,
+            &direction,
+            2.0,
+            10.0,
+            90.0,
+            &SpectralShape::Jonswap { gamma: 7.0 },
+            &spreading,
+        );
+
+        assert!(narrow.goda_peakedness(None).get_value() > broad.goda_peakedness(None).get_value());
+        assert!(
+            narrow.spectral_bandwidth(None).get_value()
+                < broad.spectral_bandwidth(None).get_value()
+        );
+        assert!(
+            narrow.wave_group_length(None).get_value() > broad.wave_group_length(None).get_value()
+        );
+        assert!(narrow.spectral_narrowness(None).get_value() < 1.0);
+
+        let hm0 = broad.hm0(None).get_value();
+        assert!((broad.h1_3(None).get_value() / hm0 - 1.001).abs() < 1e-3);
+        assert!(broad.h1_10(None).get_value() > hm0);
+        assert!(broad.hmax(3.0 * 3600.0, None).get_value() > 1.8 * hm0);
+        assert!(broad.hmax(1.0, None).value.is_none());
+    }
+
+    #[test]
+    fn test_wave_power() {
+        let spectra = gaussian_spectra(2.0, 0.1, 90.0, DirectionConvention::From);
+
+        // A narrow spectrum carries close to the power of a single wave at its peak
+        let power = spectra.wave_power(None, None).get_value();
+        let expected = 1029.0 * 9.81 * 0.25 * (0.5 * 1.56 / 0.1) / 1000.0;
+        assert!((power / expected - 1.0).abs() < 0.05);
+        assert!(spectra.wave_power(Some(3.0), None).get_value() < power);
+        assert!(spectra.wave_power(None, Some((0.5, 0.6))).value.is_none());
+
+        let density = spectra.energy_density(None).get_value();
+        assert!((density - 1029.0 * 9.81 * 0.25).abs() / density < 0.01);
+    }
+
+    #[test]
+    fn test_surface_elevation() {
+        let spectra = gaussian_spectra(1.5, 0.1, 90.0, DirectionConvention::From);
+        let elevation = spectra.surface_elevation(3600.0, 0.5, Some(11));
+        assert_eq!(elevation.len(), 7201);
+        assert_eq!(elevation, spectra.surface_elevation(3600.0, 0.5, Some(11)));
+
+        let waves = zero_crossing_analysis(&elevation, 0.5);
+        let h1_3 = waves.h1_3().unwrap();
+        assert!((h1_3 / spectra.hm0(None).get_value() - 1.0).abs() < 0.15);
+        assert!(waves.hmax().unwrap() > h1_3);
+    }
+
+    #[test]
+    fn test_bulk_parameters_band() {
+        let spectra = gaussian_spectra(2.0, 0.08, 90.0, DirectionConvention::From);
+
+        let low = spectra.hm0(Some((0.0, 0.081))).value.unwrap();
+        let high = spectra.hm0(Some((0.081, 1.0))).value.unwrap();
+        let total = spectra.hm0(None).value.unwrap();
+        assert!(low < total && high < total);
+        assert!((low.powi(2) + high.powi(2) - total.powi(2)).abs() < 1e-6);
+
+        assert!(spectra.hm0(Some((0.4, 0.5))).value.unwrap() < 0.01);
+        assert!(spectra.hm0(Some((0.6, 0.7))).value.is_none());
+        assert!(spectra.peak_frequency(Some((0.6, 0.7))).value.is_none());
+    }
+
+    #[test]
+    fn test_bulk_direction_convention() {
+        let spectra = gaussian_spectra(1.0, 0.1, 90.0, DirectionConvention::Met);
+        let dm = spectra.mean_direction(None).value.unwrap();
+        assert!((dm - 180.0).abs() < 0.1);
+
+        let spectra = gaussian_spectra(1.0, 0.1, 90.0, DirectionConvention::Towards);
+        let dp = spectra.peak_direction(None).value.unwrap();
+        assert!((dp - 270.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_parametric_partition() {
+        let (frequency, direction) = parametric_grid(36);
+
+        let swell = parametric_spectra(1.5, 14.0, 200.0, 36);
+        assert!((swell.hm0(None).value.unwrap() - 1.5).abs() < 1e-6);
+
+        let wind_sea = Spectra::from_parametric(
+            &frequency,
+            &direction,
+            1.0,
+            6.0,
+            45.0,
+            &SpectralShape::PiersonMoskowitz,
+            &DirectionalSpreading::WrappedNormal { sigma: 30.0 },
+        );
+
+        let combined = Spectra::new(
+            swell.frequency.clone(),
+            swell.direction_raw(),
+            swell
+                .energy
+                .iter()
+                .zip(wind_sea.energy.iter())
+                .map(|(a, b)| a + b)
+                .collect(),
+            DirectionConvention::From,
+        );
+
+        let partitions = combined.partition(100, None).unwrap();
+        assert!(partitions.1 >= 2);
+
+        let swell_data = combined.swell_data(None, None, None, &partitions).unwrap();
+        let primary = &swell_data.components[0];
+        let secondary = &swell_data.components[1];
+
+        assert!((primary.wave_height.value.unwrap() - 1.5).abs() < 0.2);
+        assert!((primary.period.value.unwrap() - 14.0).abs() < 1.0);
+        assert!((primary.direction.value.as_ref().unwrap().degrees - 200).abs() <= 10);
+
+        assert!((secondary.wave_height.value.unwrap() - 1.0).abs() < 0.2);
+        assert!((secondary.period.value.unwrap() - 6.0).abs() < 0.5);
+        assert!((secondary.direction.value.as_ref().unwrap().degrees - 45).abs() <= 10);
+    }
+
+    #[test]
+    fn test_partition_levels() {
+        let spectra = parametric_spectra(1.5, 14.0, 200.0, 36);
+        assert!(matches!(
+            spectra.partition(300, None),
+            Err(WatershedError::TooManyLevels(300))
+        ));
+        assert!(spectra.partition(255, None).is_ok());
+    }
+
+    #[test]
+    fn test_partition_contours() {
+        let swell = gaussian_spectra(1.5, 0.07, 200.0, DirectionConvention::From);
+        let wind_sea = gaussian_spectra(1.0, 0.18, 45.0, DirectionConvention::From);
+        let combined = swell.add(&wind_sea).unwrap();
+
+        let partitions = combined.partition(100, None).unwrap();
+        let swell_data = combined.swell_data(None, None, None, &partitions).unwrap();
+        let contours = combined
+            .partition_contours(&partitions, &swell_data)
+            .unwrap();
+
+        let GeoJson::FeatureCollection(collection) = contours else {
+            panic!("expected a feature collection");
+        };
+        assert_eq!(collection.features.len(), swell_data.components.len());
+
+        for (feature, component) in collection.features.iter().zip(swell_data.components.iter()) {
+            assert_eq!(
+                feature.property("partition").and_then(|p| p.as_u64()),
+                component.partition.map(|p| p as u64)
+            );
+            let period = feature.property("period").and_then(|p| p.as_f64()).unwrap();
+            assert!((period - component.period.get_value()).abs() < 1e-9);
+            assert!(feature.property("wave_height").is_some());
+            assert!(feature.property("energy").is_some());
+        }
+    }
+
+    #[test]
+    fn test_partition_contours_wrap_north() {
+        let swell = gaussian_spectra(1.5, 0.07, 0.0, DirectionConvention::From);
+        let wind_sea = gaussian_spectra(1.0, 0.18, 180.0, DirectionConvention::From);
+        let combined = swell.add(&wind_sea).unwrap();
+
+        let partitions = combined.partition(100, None).unwrap();
+        let swell_data = combined.swell_data(None, None, None, &partitions).unwrap();
+        let north = swell_data
+            .components
+            .iter()
+            .find(|c| {
+                c.direction.get_value().degrees <= 10 || c.direction.get_value().degrees >= 350
+            })
+            .unwrap();
+
+        // The swell straddles the end of the direction grid
+        let partition = north.partition.unwrap() as i32;
+        let nk = combined.nk();
+        let nth = combined.nth();
+        let ik = combined
+            .frequency
+            .iter()
+            .position(|f| (f - 0.07).abs() < 1e-9)
+            .unwrap();
+        assert_eq!(partitions.0[ik], partition);
+        assert_eq!(partitions.0[ik + (nth - 1) * nk], partition);
+
+        let contours = combined
+            .partition_contours(&partitions, &swell_data)
+            .unwrap();
+        let GeoJson::FeatureCollection(collection) = contours else {
+            panic!("expected a feature collection");
+        };
+        assert_eq!(collection.features.len(), swell_data.components.len());
+        assert!(collection.features.iter().any(|f| {
+            f.property("partition").and_then(|p| p.as_i64()) == Some(partition as i64)
+        }));
+
+        // The wrapped direction column lands on the seam one full turn after the first bin
+        let seam = combined.contour_coordinates(&[0.0, nth as f64]);
+        assert!((seam[1] - 360.0).abs() < 1e-9);
+        let half = combined.contour_coordinates(&[0.0, nth as f64 - 0.5]);
+        assert!((half[1] - 357.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parametric_components() {
+        let (frequency, direction) = parametric_grid(36);
+
+        let swell = Swell::new(
+            &UnitSystem::Metric,
+            2.0,
+            15.0,
+            Direction::from_degrees(290),
+            None,
+            None,
+            None,
+        );
+        let mut wind_sea = Swell::new(
+            &UnitSystem::Metric,
+            1.0,
+            5.0,
+            Direction::from_degrees(90),
+            None,
+            None,
+            None,
+        );
+        wind_sea.to_units(&UnitSystem::English);
+
+        let spectra = Spectra::from_parametric_components(
+            &frequency,
+            &direction,
+            &[
+                (
+                    swell,
+                    SpectralShape::Jonswap { gamma: 6.0 },
+                    DirectionalSpreading::CosineSquared { s: 40.0 },
+                ),
+                (
+                    wind_sea,
+                    SpectralShape::Jonswap { gamma: 3.3 },
+                    DirectionalSpreading::CosineSquared { s: 10.0 },
+                ),
+            ],
+        );
+
+        // Energy is conserved per component
+        let hs = spectra.hm0(None).value.unwrap();
+        assert!((hs - (2.0f64.powi(2) + 1.0f64.powi(2)).sqrt()).abs() < 1e-6);
+
+        let swell_band = spectra.hm0(Some((0.0, 0.1))).value.unwrap();
+        assert!((swell_band - 2.0).abs() < 0.15);
+
+        let dp = spectra.peak_direction(Some((0.0, 0.1))).value.unwrap();
+        assert!((dp - 290.0).abs() < 0.5);
+
+        // The swell tail slightly pulls the wind sea peak direction
+        let dp = spectra.peak_direction(Some((0.15, 1.0))).value.unwrap();
+        assert!((dp - 90.0).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_transform_to_depth() {
+        let (frequency, direction) = parametric_grid(72);
+        let offshore = parametric_spectra(2.0, 12.0, 300.0, 72);
+
+        // Deep water leaves the spectra untouched
+        let Ok(deep) = offshore.transform_to_depth(5000.0, 300.0) else {
+            panic!("failed to transform spectra");
+        };
+        let hs = deep.hm0(None).value.unwrap();
+        assert!((hs - 2.0).abs() < 0.05);
+
+        // Shallow water refracts toward the shore normal
+        let Ok(shallow) = offshore.transform_to_depth(5.0, 270.0) else {
+            panic!("failed to transform spectra");
+        };
+        let dm = shallow.mean_direction(None).value.unwrap();
+        assert!(dm > 270.0 && dm < 290.0);
+
+        let partitions = shallow.partition(100, None).unwrap();
+        assert!(shallow
+            .swell_data(Some(5.0), None, None, &partitions)
+            .is_ok());
+
+        // Energy travelling offshore is dropped
+        let Ok(offshore_directed) = offshore.transform_to_depth(5.0, 120.0) else {
+            panic!("failed to transform spectra");
+        };
+        assert!(offshore_directed.hm0(None).value.unwrap() < 0.05);
+
+        // The same spectra stored with Met and Towards bins transforms identically
+        let reindexed = |convention: DirectionConvention| {
+            let mut energy = vec![0.0; offshore.energy.len()];
+            for (ith, dir) in direction.iter().enumerate() {
+                let from_ith = (convention.normalize(*dir) / 5.0).round() as usize % 72;
+                energy[ith * 40..(ith + 1) * 40]
+                    .copy_from_slice(&offshore.energy[from_ith * 40..(from_ith + 1) * 40]);
+            }
+            Spectra::new(
+                frequency.clone(),
+                direction.iter().map(|d| d.to_radians()).collect(),
+                energy,
+                convention,
+            )
+        };
+
+        let expected_hs = shallow.hm0(None).value.unwrap();
+        for convention in [DirectionConvention::Met, DirectionConvention::Towards] {
+            let Ok(transformed) = reindexed(convention).transform_to_depth(5.0, 270.0) else {
+                panic!("failed to transform spectra");
+            };
+            assert_eq!(transformed.dir_convention, DirectionConvention::From);
+            assert!(transformed.dth().iter().all(|d| *d > 0.0));
+            assert!((transformed.hm0(None).value.unwrap() - expected_hs).abs() < 1e-6);
+            assert!((transformed.mean_direction(None).value.unwrap() - dm).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_spectra_arithmetic() {
+        let (frequency, direction) = parametric_grid(72);
+        let a = parametric_spectra(2.0, 12.0, 300.0, 72);
+
+        let sum = a.add(&a).unwrap();
+        assert!((sum.hm0(None).value.unwrap() - 2.0 * 2.0f64.sqrt()).abs() < 1e-6);
+        assert_eq!(a.scale(2.0).energy, sum.energy);
+
+        let difference = sum.subtract(&a).unwrap();
+        assert!((difference.hm0(None).value.unwrap() - 2.0).abs() < 1e-6);
+
+        // Different grid and convention is interpolated onto the first spectra
+        let met_direction = (0..36)
+            .map(|i| (i as f64 * 10.0).to_radians())
+            .collect::<Vec<_>>();
+        let met_frequency = (0..30).map(|i| 0.04 * 1.1f64.powi(i)).collect::<Vec<_>>();
+        let b = a.interpolate_to_grid(
+            &met_frequency,
+            &(0..36).map(|i| i as f64 * 10.0).collect::<Vec<_>>(),
+        );
+        let b = Spectra::new(
+            met_frequency.clone(),
+            met_direction,
+            (0..36)
+                .flat_map(|ith| {
+                    let from = (270.0 - ith as f64 * 10.0 + 360.0) % 360.0;
+                    let from_ith = (from / 10.0).round() as usize % 36;
+                    let b = &b;
+                    (0..met_frequency.len()).map(move |ik| b.energy_at(ik, from_ith))
+                })
+                .collect(),
+            DirectionConvention::Met,
+        );
+
+        let difference = a.subtract(&b).unwrap();
+        assert_eq!(difference.nk(), a.nk());
+        assert_eq!(difference.nth(), a.nth());
+        let residual =
+            difference.energy.iter().map(|e| e.abs()).sum::<f64>() / a.energy.iter().sum::<f64>();
+        assert!(residual < 0.1);
+
+        // Bins outside of the frequency range of the other spectra have no energy to subtract
+        let narrow_frequency = (0..25).map(|i| 0.04 * 1.07f64.powi(i)).collect::<Vec<_>>();
+        let narrow = a.interpolate_to_grid(&narrow_frequency, &direction);
+        let difference = a.subtract(&narrow).unwrap();
+        let narrow_max = narrow_frequency[narrow_frequency.len() - 1];
+        for (ik, f) in frequency.iter().enumerate() {
+            if *f >= 0.04 && *f <= narrow_max {
+                continue;
+            }
+            for ith in 0..a.nth() {
+                assert_eq!(difference.energy_at(ik, ith), a.energy_at(ik, ith));
+            }
+        }
+        assert!(frequency.iter().filter(|f| **f > narrow_max).count() > 10);
+
+        // One dimensional spectra cannot be rotated into another convention
+        let oned = Spectra::new(
+            frequency.clone(),
+            vec![0.0],
+            vec![1.0; 40],
+            DirectionConvention::Met,
+        );
+        assert!(matches!(
+            a.add(&oned),
+            Err(super::SpectraError::DirectionConventionMismatch)
+        ));
+
+        let invalid = Spectra::new(
+            frequency,
+            direction,
+            vec![1.0; 10],
+            DirectionConvention::From,
+        );
+        assert!(matches!(
+            a.add(&invalid),
+            Err(super::SpectraError::InvalidGrid(_))
+        ));
+    }
+
+    #[test]
+    fn test_separate_wind_sea() {
+        let (frequency, direction) = parametric_grid(36);
+
+        let spectra = Spectra::from_parametric_components(
+            &frequency,
+            &direction,
+            &[
+                (
+                    Swell::new(
+                        &UnitSystem::Metric,
+                        1.5,
+                        14.0,
+                        Direction::from_degrees(200),
+                        None,
+                        None,
+                        None,
+                    ),
+                    SpectralShape::Jonswap { gamma: 6.0 },
+                    DirectionalSpreading::CosineSquared { s: 40.0 },
+                ),
+                (
+                    Swell::new(
+                        &UnitSystem::Metric,
+                        1.0,
+                        5.0,
+                        Direction::from_degrees(40),
+                        None,
+                        None,
+                        None,
+                    ),
+                    SpectralShape::Jonswap { gamma: 3.3 },
+                    DirectionalSpreading::CosineSquared { s: 10.0 },
+                ),
+            ],
+        );
+
+        let (wind_sea, swell, separation_frequency) = spectra.separate_wind_sea(12.0, 45.0, None);
+
+        // Deep water separation is where g / (2 pi f) = 1.7 U
+        let expected = 1.56 / (1.7 * 12.0);
+        assert!((separation_frequency.unwrap() - expected).abs() < 1e-6);
+
+        assert!((wind_sea.hm0(None).value.unwrap() - 1.0).abs() < 0.1);
+        assert!((swell.hm0(None).value.unwrap() - 1.5).abs() < 0.1);
+
+        let total = spectra.hm0(None).value.unwrap();
+        let recombined = wind_sea.add(&swell).unwrap().hm0(None).value.unwrap();
+        assert!((total - recombined).abs() < 1e-9);
+
+        let (wind_sea, _, separation_frequency) = spectra.separate_wind_sea(0.0, 45.0, None);
+        assert!(separation_frequency.is_none());
+        assert!(wind_sea.hm0(None).value.is_none());
+
+        // Shallow water waves never travel faster than sqrt(g h), so a strong wind outruns all of
+        // them and every component aligned with the wind is wind sea
+        let (wind_sea, _, separation_frequency) = spectra.separate_wind_sea(12.0, 45.0, Some(5.0));
+        assert!((separation_frequency.unwrap() - 0.001).abs() < 1e-12);
+        assert!(wind_sea.hm0(None).value.unwrap() > 0.9);
+    }
+
+    #[test]
+    fn test_convert_convention() {
+        let spectra = parametric_spectra(2.0, 12.0, 300.0, 36);
+
+        for convention in [DirectionConvention::Towards, DirectionConvention::Met] {
+            let converted = spectra.convert_convention(convention.clone());
+            assert_eq!(converted.dir_convention, convention);
+
+            // Raw bins are sorted and the physical directions are unchanged
+            let raw = converted.direction_raw();
+            assert!(raw.windows(2).all(|w| w[0] < w[1]));
+            assert!((converted.mean_direction(None).value.unwrap() - 300.0).abs() < 1e-6);
+            assert!((converted.hm0(None).value.unwrap() - 2.0).abs() < 1e-6);
+
+            // Converting back restores the original bins exactly
+            let restored = converted.convert_convention(DirectionConvention::From);
+            assert_eq!(restored.energy, spectra.energy);
+
+            // Spectra with the same bins in different conventions are compared bin for bin
+            let difference = converted.subtract(&spectra).unwrap();
+            assert!(difference.energy.iter().all(|e| e.abs() < 1e-12));
+        }
+
+        let towards = spectra.convert_convention(DirectionConvention::Towards);
+        let peak_ith = (0..towards.nth())
+            .max_by(|a, b| {
+                towards
+                    .energy_at(10, *a)
+                    .partial_cmp(&towards.energy_at(10, *b))
+                    .unwrap()
+            })
+            .unwrap();
+        assert!((towards.direction_raw()[peak_ith].to_degrees() - 120.0).abs() < 1e-6);
+    }
+}
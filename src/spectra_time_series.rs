@@ -0,0 +1,502 @@
+use std::thread;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::{
+        directional_spectral_wave_data_record::{
+            DirectionalEstimator, DirectionalSpectralWaveDataRecord,
+        },
+        forecast_spectral_wave_data_record::ForecastSpectralWaveDataRecord,
+        spectral_wave_data_record::SpectralWaveDataRecord,
+        swden_wave_data_record::SwdenWaveDataRecordCollection,
+    },
+    location::Location,
+    spectra::{Spectra, SpectraError},
+    swell::{SwellProviderError, SwellSummary, SwellTrack},
+    tools::{
+        analysis::WatershedError,
+        partition::PartitionConfig,
+        waves::{track_partitions, track_swell_systems},
+    },
+};
+
+/// A series of dated spectra sharing the same frequency and direction bins
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpectraTimeSeries {
+    pub dates: Vec<DateTime<Utc>>,
+    pub spectra: Vec<Spectra>,
+    /// Water depth in meters, None for deep water
+    pub depth: Option<f64>,
+    /// Wind speed in m/s and direction in degrees using DirectionConvention::From for every
+    /// time step, used to separate wind sea when partitioning
+    pub wind: Vec<Option<(f64, f64)>>,
+}
+
+impl SpectraTimeSeries {
+    /// Creates a new series, interpolating every spectra onto the bins of the first one when
+    /// the grids differ. Dates must be strictly increasing
+    pub fn new(dates: Vec<DateTime<Utc>>, spectra: Vec<Spectra>) -> Result<Self, SpectraError> {
+        if dates.len() != spectra.len() {
+            return Err(SpectraError::InvalidGrid(
+                "dates do not match the number of spectra".into(),
+            ));
+        }
+
+        if dates.windows(2).any(|w| w[1] <= w[0]) {
+            return Err(SpectraError::InvalidTimeSeries(
+                "dates must be strictly increasing".into(),
+            ));
+        }
+
+        let Some(grid) = spectra.first().cloned() else {
+            return Err(SpectraError::InvalidGrid("no spectra given".into()));
+        };
+
+        let spectra = spectra
+            .iter()
+            .map(|s| s.align_to(&grid))
+            .collect::<Result<Vec<Spectra>, SpectraError>>()?;
+        let wind = vec![None; dates.len()];
+
+        Ok(SpectraTimeSeries {
+            dates,
+            spectra,
+            depth: None,
+            wind,
+        })
+    }
+
+    /// Builds the series from the given records, sorted by date since the NDBC realtime files
+    /// list the newest observation first
+    pub fn from_directional_records(
+        records: impl IntoIterator<Item = DirectionalSpectralWaveDataRecord>,
+    ) -> Result<Self, SpectraError> {
+        let mut records = records.into_iter().collect::<Vec<_>>();
+        records.sort_by_key(|r| r.date);
+
+        let (dates, spectra) = records.into_iter().map(|r| (r.date, r.spectra)).unzip();
+        Self::new(dates, spectra)
+    }
+
+    /// Builds the series from the NDBC realtime spectral files, data_spec, swdir, swdir2, swr1
+    /// and swr2, using the given direction bins in radians
+    pub fn from_data_records(
+        direction: &[f64],
+        energy_spectra: impl IntoIterator<Item = SpectralWaveDataRecord>,
+        mean_wave_direction: impl IntoIterator<Item = SpectralWaveDataRecord>,
+        primary_wave_direction: impl IntoIterator<Item = SpectralWaveDataRecord>,
+        first_polar_coefficient: impl IntoIterator<Item = SpectralWaveDataRecord>,
+        second_polar_coefficient: impl IntoIterator<Item = SpectralWaveDataRecord>,
+        estimator: &DirectionalEstimator,
+    ) -> Result<Self, SpectraError> {
+        let records = itertools::izip!(
+            energy_spectra,
+            mean_wave_direction,
+            primary_wave_direction,
+            first_polar_coefficient,
+            second_polar_coefficient,
+        )
+        .map(|(e, mwd, pwd, r1, r2)| {
+            DirectionalSpectralWaveDataRecord::from_data_records_with_estimator(
+                direction, e, mwd, pwd, r1, r2, estimator,
+            )
+        });
+
+        Self::from_directional_records(records)
+    }
+
+    /// Builds the series from the NDBC historical swden dataset, using the given direction bins
+    /// in radians
+    pub fn from_swden_records<'a>(
+        direction: &[f64],
+        collection: &'a SwdenWaveDataRecordCollection<'a>,
+        estimator: &DirectionalEstimator,
+    ) -> Result<Self, SpectraError> {
+        let records = collection.records().map(|r| {
+            DirectionalSpectralWaveDataRecord::new_with_estimator(
+                &r.date,
+                direction,
+                &r.frequency,
+                &r.energy_spectra,
+                &r.mean_wave_direction,
+                &r.primary_wave_direction,
+                &r.first_polar_coefficient,
+                &r.second_polar_coefficient,
+                estimator,
+            )
+        });
+
+        Self::from_directional_records(records)
+    }
+
+    /// Builds the series from the records of a WW3 spectral output file for a single point,
+    /// keeping the depth and wind of every record. Records from more than one location are
+    /// rejected, see `from_multi_point_forecast_records`
+    pub fn from_forecast_records(
+        records: impl IntoIterator<Item = ForecastSpectralWaveDataRecord>,
+    ) -> Result<Self, SpectraError> {
+        let records = records.into_iter().collect::<Vec<_>>();
+        if records.iter().any(|r| r.location != records[0].location) {
+            return Err(SpectraError::InvalidTimeSeries(
+                "records come from more than one location".into(),
+            ));
+        }
+
+        let mut depth = None;
+        let mut wind = Vec::new();
+        let (dates, spectra) = records
+            .into_iter()
+            .map(|r| {
+                depth = depth.or(r.depth.value);
+                wind.push(
+                    r.wind_speed
+                        .value
+                        .zip(r.wind_direction.value.as_ref().map(|d| d.degrees as f64)),
+                );
+                (r.date, r.spectra)
+            })
+            .unzip();
+
+        let mut series = Self::new(dates, spectra)?;
+        series.depth = depth;
+        series.wind = wind;
+        Ok(series)
+    }
+
+    /// Builds a series for every point of a WW3 spectral output file, in the order the points
+    /// first appear
+    pub fn from_multi_point_forecast_records(
+        records: impl IntoIterator<Item = ForecastSpectralWaveDataRecord>,
+    ) -> Result<Vec<(Location, Self)>, SpectraError> {
+        let mut points: Vec<(Location, Vec<ForecastSpectralWaveDataRecord>)> = Vec::new();
+        for record in records {
+            match points.iter_mut().find(|(l, _)| *l == record.location) {
+                Some((_, point_records)) => point_records.push(record),
+                None => points.push((record.location.clone(), vec![record])),
+            }
+        }
+
+        points
+            .into_iter()
+            .map(|(location, records)| Ok((location, Self::from_forecast_records(records)?)))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.spectra.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spectra.is_empty()
+    }
+
+    /// Runs the given function on every spectra in order, spreading the work across the
+    /// available threads when parallel is set
+    fn map_spectra<T, E, F>(&self, parallel: bool, f: F) -> Result<Vec<T>, E>
+    where
+        T: Send,
+        E: Send,
+        F: Fn(usize, &Spectra) -> Result<T, E> + Sync,
+    {
+        let threads = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1);
+        if !parallel || threads < 2 || self.len() < 2 {
+            return self
+                .spectra
+                .iter()
+                .enumerate()
+                .map(|(i, s)| f(i, s))
+                .collect();
+        }
+
+        let chunk_size = self.len().div_ceil(threads);
+        let f = &f;
+        thread::scope(|scope| {
+            let handles = self
+                .spectra
+                .chunks(chunk_size)
+                .enumerate()
+                .map(|(ic, chunk)| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .enumerate()
+                            .map(|(i, s)| f(ic * chunk_size + i, s))
+                            .collect::<Vec<Result<T, E>>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().unwrap())
+                .collect()
+        })
+    }
+
+    /// Partitions every spectra in the series with the given config
+    pub fn partition(
+        &self,
+        config: &PartitionConfig,
+        parallel: bool,
+    ) -> Result<Vec<(Vec<i32>, usize)>, WatershedError> {
+        self.map_spectra(parallel, |i, spectra| {
+            let (wind_speed, wind_direction) = self.wind[i].unzip();
+            spectra.partition_with_config(config, self.depth, wind_speed, wind_direction)
+        })
+    }
+
+    /// Partitions every spectra in the series with the given config and extracts the swell
+    /// components for every time step
+    pub fn swell_data(
+        &self,
+        config: &PartitionConfig,
+        parallel: bool,
+    ) -> Result<Vec<SwellSummary>, SwellProviderError> {
+        self.map_spectra(parallel, |i, spectra| {
+            let (wind_speed, wind_direction) = self.wind[i].unzip();
+            let partitions = spectra
+                .partition_with_config(config, self.depth, wind_speed, wind_direction)
+                .map_err(|_| {
+                    SwellProviderError::SwellPartitionError("Failed to partition spectra".into())
+                })?;
+            spectra.swell_data(self.depth, wind_speed, wind_direction, &partitions)
+        })
+    }
+
+    /// Extracts the swell components for every time step and tracks them through time. The
+    /// partition of every returned component is replaced with an id that is stable across the
+    /// series. See `track_partitions` for the meaning of the tracking parameters
+    pub fn track_swell(
+        &self,
+        config: &PartitionConfig,
+        parallel: bool,
+        max_dir_delta: f64,
+        swell_source_distance: f64,
+    ) -> Result<Vec<SwellSummary>, SwellProviderError> {
+        let summaries = self.swell_data(config, parallel)?;
+        let inputs = self
+            .dates
+            .iter()
+            .zip(summaries.iter())
+            .map(|(date, summary)| (*date, summary.components.clone()))
+            .collect::<Vec<_>>();
+
+        let tracked = track_partitions(&inputs, max_dir_delta, swell_source_distance);
+
+        Ok(summaries
+            .into_iter()
+            .zip(tracked)
+            .map(|(summary, components)| SwellSummary {
+                summary: summary.summary,
+                components,
+            })
+            .collect())
+    }
+
+    /// Extracts the swell components for every time step and groups them into swell tracks.
+    /// See `track_partitions` for the meaning of the tracking parameters
+    pub fn swell_tracks(
+        &self,
+        config: &PartitionConfig,
+        parallel: bool,
+        max_dir_delta: f64,
+        swell_source_distance: f64,
+    ) -> Result<Vec<SwellTrack>, SwellProviderError> {
+        let summaries = self.swell_data(config, parallel)?;
+        let inputs = self
+            .dates
+            .iter()
+            .zip(summaries)
+            .map(|(date, summary)| (*date, summary.components))
+            .collect::<Vec<_>>();
+
+        Ok(track_swell_systems(
+            &inputs,
+            max_dir_delta,
+            swell_source_distance,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use crate::{
+        data::forecast_spectral_wave_data_record::ForecastSpectralWaveRecordIterator,
+        spectra::{Spectra, SpectraError},
+        swell::SwellTrend,
+        tools::{
+            parametric::{DirectionalSpreading, SpectralShape},
+            partition::PartitionConfig,
+        },
+    };
+
+    use super::SpectraTimeSeries;
+
+    fn series() -> SpectraTimeSeries {
+        let frequency = (0..40)
+            .map(|i| 0.04 + i as f64 * 0.01)
+            .collect::<Vec<f64>>();
+        let direction = (0..36).map(|i| i as f64 * 10.0).collect::<Vec<f64>>();
+        let start = Utc.with_ymd_and_hms(2023, 9, 18, 0, 0, 0).unwrap();
+
+        let (dates, spectra) = (0..6)
+            .map(|i| {
+                let spectra = Spectra::from_parametric(
+                    &frequency,
+                    &direction,
+                    1.0 + 0.1 * i as f64,
+                    14.0 - 0.2 * i as f64,
+                    120.0,
+                    &SpectralShape::Jonswap { gamma: 3.3 },
+                    &DirectionalSpreading::CosineSquared { s: 20.0 },
+                )
+                .add(&Spectra::from_parametric(
+                    &frequency,
+                    &direction,
+                    0.8,
+                    6.0,
+                    250.0,
+                    &SpectralShape::Jonswap { gamma: 3.3 },
+                    &DirectionalSpreading::CosineSquared { s: 20.0 },
+                ))
+                .unwrap();
+                (start + Duration::hours(3 * i), spectra)
+            })
+            .unzip();
+
+        SpectraTimeSeries::new(dates, spectra).unwrap()
+    }
+
+    #[test]
+    fn test_parallel_partition() {
+        let series = series();
+        let config = PartitionConfig::watershed(100, None);
+
+        let serial = series.partition(&config, false).unwrap();
+        let parallel = series.partition(&config, true).unwrap();
+        assert_eq!(serial, parallel);
+        assert_eq!(serial.len(), 6);
+    }
+
+    #[test]
+    fn test_track_swell() {
+        let series = series();
+        let tracked = series
+            .track_swell(&PartitionConfig::watershed(100, None), true, 30.0, 1e6)
+            .unwrap();
+        assert_eq!(tracked.len(), 6);
+
+        // The long period swell keeps the same id through the series
+        let ids = tracked
+            .iter()
+            .map(|s| {
+                s.components
+                    .iter()
+                    .find(|c| c.period.get_value() > 10.0)
+                    .and_then(|c| c.partition)
+            })
+            .collect::<Vec<_>>();
+        assert!(ids[0].is_some());
+        assert!(ids.iter().all(|id| *id == ids[0]));
+    }
+
+    #[test]
+    fn test_swell_tracks() {
+        let series = series();
+        let tracks = series
+            .swell_tracks(&PartitionConfig::watershed(100, None), false, 30.0, 1e6)
+            .unwrap();
+
+        let groundswell = tracks.iter().find(|t| t.period()[0].1 > 10.0).unwrap();
+        assert_eq!(groundswell.series.len(), 6);
+        assert_eq!(groundswell.start(), series.dates.first().copied());
+        assert_eq!(groundswell.end(), series.dates.last().copied());
+        assert_eq!(groundswell.peak_time(), series.dates.last().copied());
+        assert_eq!(groundswell.trend(), SwellTrend::Building);
+
+        let wind_swell = tracks.iter().find(|t| t.period()[0].1 < 8.0).unwrap();
+        assert_eq!(wind_swell.trend(), SwellTrend::Steady);
+    }
+
+    #[test]
+    fn test_forecast_records_by_location() {
+        let data = "'WAVEWATCH III SPECTRA'     2     2     2 'spectral resolution for points'
+ 0.350E-01 0.600E-01
+  0.785E+00  0.393E+01
+20220918 000000
+'44097     '  40.97 -71.13      46.5   5.49 222.0   0.00 270.0
+  0.100E+00  0.200E+00  0.300E+00  0.400E+00
+'44013     '  42.35 -70.65      64.0  10.20  45.0   0.10  90.0
+  0.400E+00  0.300E+00  0.200E+00  0.100E+00
+20220918 030000
+'44097     '  40.97 -71.13      46.5   6.01 230.0   0.00 270.0
+  0.200E+00  0.300E+00  0.400E+00  0.500E+00
+'44013     '  42.35 -70.65      64.0   9.80  50.0   0.10  90.0
+  0.500E+00  0.400E+00  0.300E+00  0.200E+00
+";
+        let records = || {
+            ForecastSpectralWaveRecordIterator::from_data(data)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+
+        // Interleaving the points would repeat every date
+        assert!(matches!(
+            SpectraTimeSeries::from_forecast_records(records()),
+            Err(SpectraError::InvalidTimeSeries(_))
+        ));
+
+        let points = SpectraTimeSeries::from_multi_point_forecast_records(records()).unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].0.name, "44097");
+        assert_eq!(points[1].0.name, "44013");
+        for (_, series) in points.iter() {
+            assert_eq!(series.len(), 2);
+            assert!(series.dates[0] < series.dates[1]);
+        }
+        assert_eq!(points[1].1.depth, Some(64.0));
+        assert_eq!(points[1].1.wind[1], Some((9.8, 50.0)));
+    }
+
+    #[test]
+    fn test_rejects_unordered_dates() {
+        let series = series();
+
+        let mut dates = series.dates.clone();
+        dates[2] = dates[1];
+        assert!(matches!(
+            SpectraTimeSeries::new(dates, series.spectra.clone()),
+            Err(SpectraError::InvalidTimeSeries(_))
+        ));
+
+        let mut dates = series.dates.clone();
+        dates.reverse();
+        assert!(SpectraTimeSeries::new(dates, series.spectra).is_err());
+    }
+
+    #[test]
+    fn test_common_grid() {
+        let mut series = series();
+        let coarse = series.spectra[0].interpolate_to_grid(
+            &(0..20)
+                .map(|i| 0.05 + i as f64 * 0.02)
+                .collect::<Vec<f64>>(),
+            &(0..24).map(|i| i as f64 * 15.0).collect::<Vec<f64>>(),
+        );
+        series.spectra[1] = coarse;
+
+        let regridded = SpectraTimeSeries::new(series.dates, series.spectra).unwrap();
+        assert_eq!(
+            regridded.spectra[1].frequency,
+            regridded.spectra[0].frequency
+        );
+        assert_eq!(regridded.spectra[1].nth(), 36);
+    }
+}
@@ -1,6 +1,10 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::dimensional_data::DimensionalData;
+use crate::location::Location;
+use crate::tools::math::linear_regression;
+use crate::tools::waves::{group_velocity, swell_source_distance, wave_power, ENERGY_PERIOD_RATIO};
 use crate::units::{Direction, Unit, UnitConvertible, UnitSystem};
 use std::collections::HashMap;
 use std::fmt::{self, Debug, Display};
@@ -62,6 +66,41 @@ impl Swell {
     }
 }
 
+impl Swell {
+    /// Wave power in kW/m, treating the swell period as a peak period. When depth in meters is
+    /// not supplied use deep water approximation
+    pub fn wave_power(&self, depth: Option<f64>) -> DimensionalData<f64> {
+        let value = match (self.wave_height.value, self.period.value) {
+            (Some(height), Some(period)) => {
+                let height = self.wave_height.unit.convert(height, &Unit::Meters);
+                Some(wave_power(height, ENERGY_PERIOD_RATIO * period, depth))
+            }
+            _ => None,
+        };
+
+        DimensionalData {
+            value,
+            variable_name: "wave power".into(),
+            unit: Unit::KiloWattsPerMeter,
+        }
+    }
+}
+
+/// Total wave power in kW/m of a set of swell components, which is the sum of the power of
+/// every component. When depth in meters is not supplied use deep water approximation
+pub fn total_wave_power(components: &[Swell], depth: Option<f64>) -> DimensionalData<f64> {
+    let value = components
+        .iter()
+        .filter_map(|c| c.wave_power(depth).value)
+        .reduce(|a, b| a + b);
+
+    DimensionalData {
+        value,
+        variable_name: "wave power".into(),
+        unit: Unit::KiloWattsPerMeter,
+    }
+}
+
 impl UnitConvertible for Swell {
     fn to_units(&mut self, new_units: &UnitSystem) -> &mut Self {
         self.wave_height.to_units(new_units);
@@ -170,3 +209,369 @@ impl UnitConvertible for SwellSummary {
         self
     }
 }
+
+/// Relative change in wave height over the end of a track below which the swell is steady
+const TREND_THRESHOLD: f64 = 0.05;
+
+/// Number of samples at the end of a track used to find its trend
+const TREND_WINDOW: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwellTrend {
+    Building,
+    Steady,
+    Fading,
+}
+
+/// Estimated origin of a swell, inferred from the dispersion of its peak frequency
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwellSource {
+    /// Estimated time the swell was generated at the source
+    pub generation_time: DateTime<Utc>,
+    /// Great circle distance to the source in kilometers
+    pub distance: f64,
+    /// Mean direction the swell arrives from in degrees
+    pub direction: f64,
+    /// Estimated location of the generating storm
+    pub location: Location,
+    /// Rate the peak frequency rises at in hz per second
+    pub dispersion_rate: f64,
+    /// Coefficient of determination of the linear fit of the peak frequency over time
+    pub r_squared: f64,
+}
+
+/// A single swell system tracked through time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwellTrack {
+    /// Track id, matching the partition of every swell in the series
+    pub id: usize,
+    /// The swell component at every time step of the track, ordered by time
+    pub series: Vec<(DateTime<Utc>, Swell)>,
+}
+
+impl SwellTrack {
+    pub fn start(&self) -> Option<DateTime<Utc>> {
+        self.series.first().map(|(t, _)| *t)
+    }
+
+    pub fn end(&self) -> Option<DateTime<Utc>> {
+        self.series.last().map(|(t, _)| *t)
+    }
+
+    /// Significant wave height at every time step of the track
+    pub fn wave_height(&self) -> Vec<(DateTime<Utc>, f64)> {
+        self.series
+            .iter()
+            .map(|(t, s)| (*t, s.wave_height.get_value()))
+            .collect()
+    }
+
+    /// Peak period in seconds at every time step of the track
+    pub fn period(&self) -> Vec<(DateTime<Utc>, f64)> {
+        self.series
+            .iter()
+            .map(|(t, s)| (*t, s.period.get_value()))
+            .collect()
+    }
+
+    /// Direction in degrees at every time step of the track
+    pub fn direction(&self) -> Vec<(DateTime<Utc>, f64)> {
+        self.series
+            .iter()
+            .map(|(t, s)| (*t, s.direction.get_value().degrees as f64))
+            .collect()
+    }
+
+    /// The time step with the most energy, falling back to the wave height when the energy is
+    /// not available
+    pub fn peak(&self) -> Option<&(DateTime<Utc>, Swell)> {
+        let magnitude = |s: &Swell| {
+            s.energy
+                .as_ref()
+                .and_then(|e| e.value)
+                .unwrap_or_else(|| s.wave_height.get_value())
+        };
+
+        self.series
+            .iter()
+            .max_by(|a, b| magnitude(&a.1).total_cmp(&magnitude(&b.1)))
+    }
+
+    pub fn peak_time(&self) -> Option<DateTime<Utc>> {
+        self.peak().map(|(t, _)| *t)
+    }
+
+    /// Whether the swell is building or fading at the end of the track, from the change in wave
+    /// height over the last few time steps
+    pub fn trend(&self) -> SwellTrend {
+        let window = &self.series[self.series.len().saturating_sub(TREND_WINDOW)..];
+        let (Some(first), Some(last)) = (window.first(), window.last()) else {
+            return SwellTrend::Steady;
+        };
+
+        let mean = window
+            .iter()
+            .map(|(_, s)| s.wave_height.get_value())
+            .sum::<f64>()
+            / window.len() as f64;
+        if mean <= 0.0 {
+            return SwellTrend::Steady;
+        }
+
+        let change = (last.1.wave_height.get_value() - first.1.wave_height.get_value()) / mean;
+        if change > TREND_THRESHOLD {
+            SwellTrend::Building
+        } else if change < -TREND_THRESHOLD {
+            SwellTrend::Fading
+        } else {
+            SwellTrend::Steady
+        }
+    }
+
+    /// Infers the source of the swell from the linear rise of its peak frequency over time as
+    /// seen at the given observation location, following the dispersion relationship of
+    /// Snodgrass et al (1966). Needs at least three time steps with a rising peak frequency
+    pub fn infer_source(&self, observer: &Location) -> Option<SwellSource> {
+        let start = self.start()?;
+        if self.series.len() < 3 {
+            return None;
+        }
+
+        let (times, frequency): (Vec<f64>, Vec<f64>) = self
+            .series
+            .iter()
+            .map(|(t, s)| {
+                (
+                    (*t - start).num_seconds() as f64,
+                    1.0 / s.period.get_value(),
+                )
+            })
+            .unzip();
+
+        let (slope, intercept, r_squared) = linear_regression(&times, &frequency)?;
+        if slope <= 0.0 {
+            return None;
+        }
+
+        // The peak frequency is zero at the time the swell was generated
+        let generation_offset = -intercept / slope;
+        let generation_time = start + chrono::Duration::seconds(generation_offset.round() as i64);
+        let distance = swell_source_distance(slope) / 1000.0;
+
+        let (x, y) = self.direction().iter().fold((0.0, 0.0), |(x, y), (_, d)| {
+            (x + d.to_radians().sin(), y + d.to_radians().cos())
+        });
+        let direction = (x.atan2(y).to_degrees() + 360.0) % 360.0;
+
+        Some(SwellSource {
+            generation_time,
+            distance,
+            direction,
+            location: observer.destination(direction, distance, &UnitSystem::Metric),
+            dispersion_rate: slope,
+            r_squared,
+        })
+    }
+}
+
+/// Range of directions in degrees a spot is open to swell from, running clockwise from start to
+/// end and wrapping through north when start is greater than end
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExposureWindow {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl ExposureWindow {
+    pub fn new(start: f64, end: f64) -> Self {
+        ExposureWindow { start, end }
+    }
+
+    /// Whether swell arriving from the given direction in degrees reaches the spot
+    pub fn contains(&self, direction: f64) -> bool {
+        let direction = direction.rem_euclid(360.0);
+        let (start, end) = (self.start.rem_euclid(360.0), self.end.rem_euclid(360.0));
+        if start <= end {
+            start <= direction && direction <= end
+        } else {
+            direction >= start || direction <= end
+        }
+    }
+}
+
+/// A swell forecast to arrive at a spot after travelling from a distant storm
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwellArrival {
+    /// Arrival time of the leading energy at the spot
+    pub arrival_time: DateTime<Utc>,
+    /// Great circle distance travelled in kilometers
+    pub distance: f64,
+    /// Travel time in hours
+    pub travel_time: f64,
+    /// Whether the arrival direction falls within the exposure window of the spot
+    pub exposed: bool,
+    /// The swell at the spot, arriving from the great circle bearing to the storm
+    pub swell: Swell,
+}
+
+/// Forecasts when swell generated at the source location and time arrives at the spot,
+/// travelling along the great circle at the deep water group velocity of its period. The
+/// predicted swell keeps the wave height of the given swell, with the direction replaced by
+/// the arrival direction at the spot. Without an exposure window the spot is always exposed
+pub fn forecast_swell_arrival(
+    source: &Location,
+    generation_time: DateTime<Utc>,
+    swell: &Swell,
+    spot: &Location,
+    exposure: Option<&ExposureWindow>,
+) -> SwellArrival {
+    let period = swell.period.get_value();
+    let distance = source.distance(spot, &UnitSystem::Metric);
+    let speed = group_velocity(1.0 / period, None);
+    let travel_seconds = distance * 1000.0 / speed;
+
+    let arrival_direction = spot.bearing_to(source);
+    let exposed = exposure
+        .map(|e| e.contains(arrival_direction))
+        .unwrap_or(true);
+
+    SwellArrival {
+        arrival_time: generation_time + chrono::Duration::seconds(travel_seconds.round() as i64),
+        distance,
+        travel_time: travel_seconds / 3600.0,
+        exposed,
+        swell: Swell {
+            direction: DimensionalData {
+                value: Some(Direction::from_degrees(
+                    arrival_direction.round() as i32 % 360,
+                )),
+                variable_name: "direction".into(),
+                unit: Unit::Degrees,
+            },
+            ..swell.clone()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{
+        forecast_swell_arrival, total_wave_power, ExposureWindow, Swell, SwellTrack, SwellTrend,
+    };
+    use crate::{
+        location::Location,
+        units::{Direction, Unit, UnitConvertible, UnitSystem},
+    };
+
+    #[test]
+    fn test_infer_source() {
+        let generation_time = Utc.with_ymd_and_hms(2023, 9, 14, 0, 0, 0).unwrap();
+        let distance = 3000.0e3;
+
+        // Peak frequency rising as the dispersed swell arrives from 3000 km away
+        let series = (0..8)
+            .map(|i| {
+                let time = generation_time + Duration::hours(60 + 3 * i);
+                let elapsed = (time - generation_time).num_seconds() as f64;
+                let fp = 9.81 * elapsed / (4.0 * PI * distance);
+                let swell = Swell::new(
+                    &UnitSystem::Metric,
+                    1.0 + 0.1 * i as f64,
+                    1.0 / fp,
+                    Direction::from_degrees(if i % 2 == 0 { 358 } else { 2 }),
+                    None,
+                    None,
+                    Some(1),
+                );
+                (time, swell)
+            })
+            .collect::<Vec<_>>();
+        let track = SwellTrack { id: 1, series };
+        assert_eq!(track.trend(), SwellTrend::Building);
+
+        let observer = Location::new(40.0, -70.0, "observer".into());
+        let source = track.infer_source(&observer).unwrap();
+        assert!((source.distance - 3000.0).abs() < 1.0);
+        assert!(
+            (source.generation_time - generation_time)
+                .num_seconds()
+                .abs()
+                < 60
+        );
+        assert!(source.direction < 0.5 || source.direction > 359.5);
+        assert!((source.r_squared - 1.0).abs() < 1e-9);
+
+        // Due north of the observer
+        assert!((source.location.longitude + 70.0).abs() < 0.1);
+        assert!((source.location.latitude - 40.0 - 26.98).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_forecast_swell_arrival() {
+        let storm = Location::new(45.0, -40.0, "storm".into());
+        let spot = Location::new(41.0, -71.4, "spot".into());
+        let departure = Utc.with_ymd_and_hms(2023, 9, 14, 0, 0, 0).unwrap();
+        let swell = Swell::new(
+            &UnitSystem::Metric,
+            2.0,
+            15.0,
+            Direction::from_degrees(0),
+            None,
+            None,
+            None,
+        );
+
+        let exposure = ExposureWindow::new(90.0, 200.0);
+        let arrival = forecast_swell_arrival(&storm, departure, &swell, &spot, Some(&exposure));
+
+        // Deep water group velocity of 15 s swell is about 11.7 m/s
+        let cg = 9.81 * 15.0 / (4.0 * PI);
+        let expected = arrival.distance * 1000.0 / cg / 3600.0;
+        assert!((arrival.travel_time - expected).abs() / expected < 0.005);
+        assert!((arrival.distance - 2575.3).abs() < 1.0);
+        assert_eq!(
+            arrival.arrival_time,
+            departure + Duration::seconds((arrival.travel_time * 3600.0).round() as i64)
+        );
+
+        // Arrives out of the east north east, outside of a southerly exposure
+        let direction = arrival.swell.direction.get_value().degrees;
+        assert!((60..80).contains(&direction));
+        assert!(!arrival.exposed);
+        assert!(ExposureWindow::new(340.0, 90.0).contains(direction as f64));
+        assert!(ExposureWindow::new(340.0, 90.0).contains(355.0));
+    }
+
+    #[test]
+    fn test_wave_power() {
+        let swell = Swell::new(
+            &UnitSystem::Metric,
+            2.0,
+            10.0,
+            Direction::from_degrees(90),
+            None,
+            None,
+            None,
+        );
+
+        // Deep water power is rho * g^2 * Hs^2 * Te / (64 * pi), about 17.7 kW/m for 2 m at 9 s
+        let power = swell.wave_power(None);
+        assert_eq!(power.unit, Unit::KiloWattsPerMeter);
+        assert!((power.get_value() - 17.7).abs() < 0.1);
+
+        // Shallow water slows the groups and reduces the flux
+        assert!(swell.wave_power(Some(5.0)).get_value() < power.get_value());
+
+        let mut english = swell.clone();
+        english.to_units(&UnitSystem::English);
+        assert!((english.wave_power(None).get_value() - power.get_value()).abs() < 0.01);
+
+        let total = total_wave_power(&[swell.clone(), swell.clone()], None);
+        assert!((total.get_value() - 2.0 * power.get_value()).abs() < 1e-9);
+        assert!(total_wave_power(&[], None).value.is_none());
+    }
+}
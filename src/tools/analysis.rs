@@ -210,10 +210,64 @@ pub fn nearest_neighbors(width: usize, height: usize, index: usize) -> Vec<usize
 pub enum WatershedError {
     Unknown,
     InvalidData,
+    /// The image based watershed digitizes into a byte, so it supports at most 255 levels
+    TooManyLevels(usize),
+}
+
+/// Smooths gridded data with a separable gaussian kernel with the given standard deviation in
+/// grid cells. Columns are clamped at the edges while rows wrap around, matching the layout of
+/// spectral data where frequency is the columns and direction is the rows
+pub fn gaussian_smooth(data: &[f64], width: usize, height: usize, sigma: f64) -> Vec<f64> {
+    if sigma <= 0.0 || data.len() != width * height {
+        return data.to_vec();
+    }
+
+    let radius = (3.0 * sigma).ceil() as isize;
+    let kernel = (-radius..=radius)
+        .map(|i| (-(i as f64).powi(2) / (2.0 * sigma.powi(2))).exp())
+        .collect::<Vec<f64>>();
+
+    // Along the columns, renormalizing the kernel where it is cut off by the edges
+    let mut rows = vec![0.0; data.len()];
+    for j in 0..height {
+        for i in 0..width {
+            let mut sum = 0.0;
+            let mut weight = 0.0;
+            for (k, w) in kernel.iter().enumerate() {
+                let ii = i as isize + k as isize - radius;
+                if ii < 0 || ii >= width as isize {
+                    continue;
+                }
+                sum += w * data[j * width + ii as usize];
+                weight += w;
+            }
+            rows[j * width + i] = sum / weight;
+        }
+    }
+
+    // Along the rows, wrapping around
+    let total = kernel.iter().sum::<f64>();
+    let mut smoothed = vec![0.0; data.len()];
+    for j in 0..height {
+        for i in 0..width {
+            let sum = kernel
+                .iter()
+                .enumerate()
+                .map(|(k, w)| {
+                    let jj = (j as isize + k as isize - radius).rem_euclid(height as isize);
+                    w * rows[jj as usize * width + i]
+                })
+                .sum::<f64>();
+            smoothed[j * width + i] = sum / total;
+        }
+    }
+
+    smoothed
 }
 
 /// Implementation of watershed algorithm as used by WW3 in w3partmd.f90
-/// More details to come
+/// More details to come. The levels are digitized into a byte so at most 255 steps are
+/// supported, `smoothed_watershed` has no such limit
 pub fn watershed(
     data: &[f64],
     width: usize,
@@ -225,6 +279,9 @@ pub fn watershed(
     if data.len() != count {
         return Err(WatershedError::InvalidData);
     }
+    if steps > u8::MAX as usize {
+        return Err(WatershedError::TooManyLevels(steps));
+    }
 
     let min_value = data.iter().copied().fold(f64::INFINITY, f64::min);
     let max_value = data.iter().copied().fold(f64::NEG_INFINITY, f64::max);
@@ -257,8 +314,67 @@ pub fn watershed(
             .collect::<Vec<u8>>()
     };
 
+    let imi = imi.iter().map(|v| *v as usize).collect::<Vec<usize>>();
+    Ok(flood_levels(
+        data,
+        &imi,
+        width,
+        height,
+        steps as u8 as usize,
+        max_value,
+    ))
+}
+
+/// Watershed partitioning used by `partition_spectrum`. Unlike `watershed` the data is
+/// digitized into any number of levels, optionally after smoothing it with a gaussian kernel
+/// with the given standard deviation in grid cells. Returns the partition label for every grid
+/// cell along with the partition count, including the zero partition
+pub fn smoothed_watershed(
+    data: &[f64],
+    width: usize,
+    height: usize,
+    steps: usize,
+    smoothing: Option<f64>,
+) -> Result<(Vec<i32>, usize), WatershedError> {
+    let count = width * height;
+    if data.len() != count {
+        return Err(WatershedError::InvalidData);
+    }
+
+    let min_value = data.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_value = data.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    // Scale the data
+    let fact = (steps as f64 - 1.0) / (max_value - min_value);
+
+    // Digitize the signal, mapping each energy value to a level from 1 to steps
+    // If smoothing is specified, apply it first
+    let smoothed = match smoothing {
+        Some(sigma) => gaussian_smooth(data, width, height, sigma),
+        None => data.to_vec(),
+    };
+
+    let imi = smoothed
+        .iter()
+        .map(|v| 1usize.max(steps.min((1.0 + (max_value - v) * fact).round() as usize)))
+        .collect::<Vec<usize>>();
+
+    Ok(flood_levels(data, &imi, width, height, steps, max_value))
+}
+
+/// Floods the digitized levels from the lowest up, labelling every basin
+fn flood_levels(
+    data: &[f64],
+    imi: &[usize],
+    width: usize,
+    height: usize,
+    steps: usize,
+    max_value: f64,
+) -> (Vec<i32>, usize) {
+    let count = width * height;
+
     // Sort the digitized data indices, so all levels are grouped in order
-    let ind = argsort::<u8>(&imi);
+    let ind = argsort::<usize>(imi);
 
     // Compute the nearest neighbor for every index ahead of time
     let neigh = (0..count)
@@ -280,7 +396,7 @@ pub fn watershed(
     let mut imd = vec![0; count];
 
     // Iterate the levels looking for the watersheds
-    for ih in 1u8..=(steps as u8) {
+    for ih in 1..=steps {
         m_save = m; // 0
 
         while m < count {
@@ -405,15 +521,117 @@ pub fn watershed(
         }
     }
 
-    Ok((imo, ic_label as usize + 1))
+    (imo, ic_label as usize + 1)
+}
+
+/// Solves the rectangular assignment problem with the Hungarian algorithm, returning the
+/// column assigned to every row so that the total cost is minimized. Rows are left unassigned
+/// when there are more rows than columns. Non finite costs are treated as more expensive than
+/// any finite cost in the matrix
+pub fn hungarian(cost: &[Vec<f64>]) -> Vec<Option<usize>> {
+    let rows = cost.len();
+    let cols = cost.iter().map(|r| r.len()).max().unwrap_or(0);
+    let n = rows.max(cols);
+    if n == 0 {
+        return vec![None; rows];
+    }
+
+    // Non finite costs would keep the search from ever finding a free column, so replace them
+    // with a cost larger than any complete finite assignment
+    let max_cost = cost
+        .iter()
+        .flatten()
+        .filter(|c| c.is_finite())
+        .fold(0.0f64, |acc, c| acc.max(c.abs()));
+    let unusable = (max_cost + 1.0) * (n as f64 + 1.0);
+
+    // Pad to a square matrix with zero cost dummy rows and columns
+    let c = |i: usize, j: usize| match cost.get(i).and_then(|r| r.get(j)) {
+        Some(c) if c.is_finite() => *c,
+        Some(_) => unusable,
+        None => 0.0,
+    };
+
+    // Potentials and matching are 1 based, with 0 as the virtual starting column
+    let mut u = vec![0.0; n + 1];
+    let mut v = vec![0.0; n + 1];
+    let mut p = vec![0usize; n + 1];
+    let mut way = vec![0usize; n + 1];
+
+    for i in 1..=n {
+        p[0] = i;
+        let mut j0 = 0;
+        let mut minv = vec![f64::INFINITY; n + 1];
+        let mut used = vec![false; n + 1];
+
+        loop {
+            used[j0] = true;
+            let i0 = p[j0];
+            let mut delta = f64::INFINITY;
+            let mut j1 = 0;
+
+            for j in 1..=n {
+                if used[j] {
+                    continue;
+                }
+
+                let cur = c(i0 - 1, j - 1) - u[i0] - v[j];
+                if cur < minv[j] {
+                    minv[j] = cur;
+                    way[j] = j0;
+                }
+                if minv[j] < delta {
+                    delta = minv[j];
+                    j1 = j;
+                }
+            }
+
+            for j in 0..=n {
+                if used[j] {
+                    u[p[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    minv[j] -= delta;
+                }
+            }
+
+            j0 = j1;
+            if p[j0] == 0 {
+                break;
+            }
+        }
+
+        // Unwind the augmenting path
+        loop {
+            let j1 = way[j0];
+            p[j0] = p[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut assignment = vec![None; rows];
+    for (j, i) in p.iter().enumerate().skip(1) {
+        if *i > 0 && *i <= rows && j <= cols {
+            assignment[*i - 1] = Some(j - 1);
+        }
+    }
+
+    assignment
 }
 
 #[cfg(test)]
 mod tests {
     use super::bilerp;
+    use super::gaussian_smooth;
+    use super::hungarian;
     use super::lerp;
     use super::nearest_neighbors;
+    use super::smoothed_watershed;
     use super::watershed;
+    use super::WatershedError;
     use rand;
 
     #[test]
@@ -483,4 +701,76 @@ mod tests {
         let watershed_result = watershed(&data, WIDTH, HEIGHT, 50, None);
         assert!(watershed_result.is_ok());
     }
+
+    #[test]
+    fn test_watershed_too_many_levels() {
+        const WIDTH: usize = 6;
+        const HEIGHT: usize = 5;
+        let data: [f64; WIDTH * HEIGHT] = rand::random();
+
+        // 300 levels would wrap around when digitized into a byte
+        assert!(matches!(
+            watershed(&data, WIDTH, HEIGHT, 300, None),
+            Err(WatershedError::TooManyLevels(300))
+        ));
+        assert!(watershed(&data, WIDTH, HEIGHT, 255, None).is_ok());
+    }
+
+    #[test]
+    fn test_smoothed_watershed_levels() {
+        const WIDTH: usize = 20;
+        const HEIGHT: usize = 12;
+
+        // Two separate peaks, more levels than fit in a byte
+        let data = (0..WIDTH * HEIGHT)
+            .map(|i| {
+                let (x, y) = ((i % WIDTH) as f64, (i / WIDTH) as f64);
+                (-((x - 4.0).powi(2) + (y - 3.0).powi(2)) / 4.0).exp()
+                    + 0.5 * (-((x - 15.0).powi(2) + (y - 8.0).powi(2)) / 4.0).exp()
+            })
+            .collect::<Vec<f64>>();
+
+        for smoothing in [None, Some(0.8)] {
+            let (imo, count) = smoothed_watershed(&data, WIDTH, HEIGHT, 1000, smoothing).unwrap();
+            assert_eq!(count, 3);
+            assert_ne!(imo[3 * WIDTH + 4], imo[8 * WIDTH + 15]);
+            assert!(imo.iter().all(|l| *l > 0));
+        }
+    }
+
+    #[test]
+    fn test_gaussian_smooth() {
+        const WIDTH: usize = 5;
+        const HEIGHT: usize = 4;
+        let mut data = vec![0.0; WIDTH * HEIGHT];
+        data[2] = 1.0;
+
+        let smoothed = gaussian_smooth(&data, WIDTH, HEIGHT, 1.0);
+
+        // Symmetric across columns and wraps across rows
+        assert!((smoothed[1] - smoothed[3]).abs() < 1e-12);
+        assert!((smoothed[WIDTH + 2] - smoothed[3 * WIDTH + 2]).abs() < 1e-12);
+        assert!(smoothed[2] > smoothed[WIDTH + 2]);
+    }
+
+    #[test]
+    fn test_hungarian() {
+        // The greedy choice of row 0 -> column 0 is not optimal
+        let cost = vec![vec![1.0, 2.0], vec![1.5, 10.0]];
+        assert_eq!(hungarian(&cost), vec![Some(1), Some(0)]);
+
+        // More rows than columns leaves the most expensive row unassigned
+        let cost = vec![vec![4.0], vec![1.0], vec![3.0]];
+        assert_eq!(hungarian(&cost), vec![None, Some(0), None]);
+
+        // More columns than rows
+        let cost = vec![vec![5.0, 1.0, 3.0]];
+        assert_eq!(hungarian(&cost), vec![Some(1)]);
+
+        // Non finite costs terminate and are avoided when possible
+        let cost = vec![vec![f64::NAN, 1.0], vec![2.0, f64::INFINITY]];
+        assert_eq!(hungarian(&cost), vec![Some(1), Some(0)]);
+        let cost = vec![vec![f64::NAN, f64::NAN], vec![f64::INFINITY, f64::NAN]];
+        assert_eq!(hungarian(&cost).len(), 2);
+    }
 }
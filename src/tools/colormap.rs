@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+/// Colormaps used to map normalized values to colors when rendering data
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Colormap {
+    #[default]
+    Viridis,
+    Magma,
+    Jet,
+    Greys,
+}
+
+const VIRIDIS: [[u8; 3]; 9] = [
+    [68, 1, 84],
+    [71, 44, 122],
+    [59, 81, 139],
+    [44, 113, 142],
+    [33, 144, 141],
+    [39, 173, 129],
+    [92, 200, 99],
+    [170, 220, 50],
+    [253, 231, 37],
+];
+
+const MAGMA: [[u8; 3]; 9] = [
+    [0, 0, 4],
+    [28, 16, 68],
+    [79, 18, 123],
+    [129, 37, 129],
+    [181, 54, 122],
+    [229, 80, 100],
+    [251, 135, 97],
+    [254, 194, 135],
+    [252, 253, 191],
+];
+
+const JET: [[u8; 3]; 9] = [
+    [0, 0, 128],
+    [0, 0, 255],
+    [0, 128, 255],
+    [0, 255, 255],
+    [128, 255, 128],
+    [255, 255, 0],
+    [255, 128, 0],
+    [255, 0, 0],
+    [128, 0, 0],
+];
+
+const GREYS: [[u8; 3]; 2] = [[255, 255, 255], [0, 0, 0]];
+
+impl Colormap {
+    fn anchors(&self) -> &'static [[u8; 3]] {
+        match self {
+            Colormap::Viridis => &VIRIDIS,
+            Colormap::Magma => &MAGMA,
+            Colormap::Jet => &JET,
+            Colormap::Greys => &GREYS,
+        }
+    }
+
+    /// Maps a normalized value between 0 and 1 to an rgb color, values outside of
+    /// the range are clamped
+    pub fn color(&self, value: f64) -> [u8; 3] {
+        let anchors = self.anchors();
+        let value = if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, 1.0)
+        };
+
+        let position = value * (anchors.len() - 1) as f64;
+        let lower = position.floor() as usize;
+        let upper = (lower + 1).min(anchors.len() - 1);
+        let t = position - lower as f64;
+
+        let mut color = [0; 3];
+        for (i, c) in color.iter_mut().enumerate() {
+            let a = anchors[lower][i] as f64;
+            let b = anchors[upper][i] as f64;
+            *c = (a + (b - a) * t).round() as u8;
+        }
+
+        color
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Colormap;
+
+    #[test]
+    fn test_colormap_bounds() {
+        assert_eq!(Colormap::Viridis.color(0.0), [68, 1, 84]);
+        assert_eq!(Colormap::Viridis.color(1.0), [253, 231, 37]);
+        assert_eq!(Colormap::Viridis.color(-1.0), Colormap::Viridis.color(0.0));
+        assert_eq!(Colormap::Viridis.color(2.0), Colormap::Viridis.color(1.0));
+        assert_eq!(Colormap::Greys.color(0.5), [128, 128, 128]);
+    }
+}
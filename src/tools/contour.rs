@@ -59,10 +59,12 @@ pub fn compute_contours<C: Fn(&Vec<f64>) -> Vec<f64>, F: Fn(&usize, &f64) -> Str
             let new_polygon = Value::MultiPolygon(new_coordinates);
             f.geometry = Some(new_polygon.into());
 
-            f.set_property(
-                "normalized",
-                (&c.threshold() - thresholds.first().unwrap()) / threshold_range,
-            );
+            if threshold_range > 0.0 {
+                f.set_property(
+                    "normalized",
+                    (&c.threshold() - thresholds.first().unwrap()) / threshold_range,
+                );
+            }
 
             if let Some(formatter) = label_format.as_ref() {
                 let label = formatter(&i, &c.threshold());
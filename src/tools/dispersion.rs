@@ -0,0 +1,70 @@
+use std::f64::consts::PI;
+
+const GRAVITY: f64 = 9.81;
+
+/// Solves the linear dispersion relation omega^2 = g k tanh(kh) with newton raphson iteration,
+/// returning the wavenumber in rad/m for a wave with the given period in seconds and depth in
+/// meters, or None when the iteration does not converge. Units are metric, gravity is 9.81 m/s.
+pub fn wavenumber(period: f64, depth: f64) -> Option<f64> {
+    const EPS: f64 = 0.000001;
+    const MAX_ITERATION: usize = 50;
+
+    let omega = 2.0 * PI / period;
+    let d = omega.powi(2) * depth / GRAVITY;
+
+    let mut iter: usize = 0;
+    let mut err: f64 = 1.0;
+
+    let mut xf: f64 = 0.0;
+    let mut xo: f64;
+    let mut f: f64;
+    let mut df: f64;
+
+    // Make an initial guess for non dimensional solutions
+    if d >= 1.0 {
+        xo = d;
+    } else {
+        xo = d.sqrt();
+    }
+
+    // Solve using newton raphson iteration
+    while (err > EPS) && (iter < MAX_ITERATION) {
+        f = xo - (d / xo.tanh());
+        df = 1.0 + (d / xo.sinh().powi(2));
+        xf = xo - (f / df);
+        err = ((xf - xo) / xo).abs();
+        xo = xf;
+        iter += 1;
+    }
+
+    if iter >= MAX_ITERATION {
+        None
+    } else {
+        Some(xf / depth)
+    }
+}
+
+/// Ratio n of the group speed to the phase speed for the given relative depth kh
+pub fn group_ratio(kh: f64) -> f64 {
+    // sinh(2kh) overflows in very deep water, where n is a half
+    if kh > 20.0 {
+        0.5
+    } else {
+        0.5 * (1.0 + 2.0 * kh / (2.0 * kh).sinh())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{group_ratio, wavenumber};
+
+    #[test]
+    fn test_dispersion() {
+        // Deep water wavelength L0 = g T^2 / 2 pi
+        let k = wavenumber(10.0, 500.0).unwrap();
+        assert!((2.0 * std::f64::consts::PI / k - 156.13).abs() < 0.1);
+
+        assert!((group_ratio(50.0) - 0.5).abs() < 1e-12);
+        assert!((group_ratio(1e-4) - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,126 @@
+use std::f64::consts::PI;
+
+/// Discrete fourier transform of a complex series stored as (re, im) pairs, using the
+/// e^(-2 pi i k n / N) sign convention. Power of two lengths use an iterative radix 2 fft,
+/// any other length falls back to the direct O(N^2) transform
+pub fn fft(input: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let n = input.len();
+    if n <= 1 {
+        return input.to_vec();
+    }
+
+    if !n.is_power_of_two() {
+        return dft(input);
+    }
+
+    // Bit reversed ordering
+    let bits = n.trailing_zeros();
+    let mut output = vec![(0.0, 0.0); n];
+    for (i, value) in input.iter().enumerate() {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        output[j] = *value;
+    }
+
+    let mut size = 2;
+    while size <= n {
+        let half = size / 2;
+        let step = -2.0 * PI / size as f64;
+        for start in (0..n).step_by(size) {
+            for k in 0..half {
+                let twiddle = ((step * k as f64).cos(), (step * k as f64).sin());
+                let a = output[start + k];
+                let b = output[start + k + half];
+                let t = (
+                    b.0 * twiddle.0 - b.1 * twiddle.1,
+                    b.0 * twiddle.1 + b.1 * twiddle.0,
+                );
+                output[start + k] = (a.0 + t.0, a.1 + t.1);
+                output[start + k + half] = (a.0 - t.0, a.1 - t.1);
+            }
+        }
+        size *= 2;
+    }
+
+    output
+}
+
+/// Fourier transform of a real series, returning the N / 2 + 1 non negative frequency terms
+pub fn rfft(input: &[f64]) -> Vec<(f64, f64)> {
+    let complex = input.iter().map(|v| (*v, 0.0)).collect::<Vec<_>>();
+    let mut output = fft(&complex);
+    output.truncate(input.len() / 2 + 1);
+    output
+}
+
+/// Frequencies in hz of the terms returned by rfft for a series of the given length sampled
+/// at the given rate in hz
+pub fn rfft_frequencies(length: usize, sample_rate: f64) -> Vec<f64> {
+    (0..=length / 2)
+        .map(|k| k as f64 * sample_rate / length as f64)
+        .collect()
+}
+
+fn dft(input: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let n = input.len();
+    (0..n)
+        .map(|k| {
+            input
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |acc, (j, value)| {
+                    let angle = -2.0 * PI * ((k * j) % n) as f64 / n as f64;
+                    let (s, c) = angle.sin_cos();
+                    (
+                        acc.0 + value.0 * c - value.1 * s,
+                        acc.1 + value.0 * s + value.1 * c,
+                    )
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::{dft, fft, rfft, rfft_frequencies};
+
+    #[test]
+    fn test_fft_matches_dft() {
+        let input = (0..64)
+            .map(|i| ((i as f64 * 0.3).sin(), (i as f64 * 0.7).cos()))
+            .collect::<Vec<_>>();
+
+        let fast = fft(&input);
+        let slow = dft(&input);
+        for (a, b) in fast.iter().zip(slow.iter()) {
+            assert!((a.0 - b.0).abs() < 1e-9);
+            assert!((a.1 - b.1).abs() < 1e-9);
+        }
+
+        // Non power of two lengths still transform
+        assert_eq!(fft(&input[0..48]).len(), 48);
+    }
+
+    #[test]
+    fn test_rfft_peak() {
+        let sample_rate = 2.0;
+        let input = (0..256)
+            .map(|i| (2.0 * PI * 0.125 * i as f64 / sample_rate).cos())
+            .collect::<Vec<f64>>();
+
+        let output = rfft(&input);
+        let frequency = rfft_frequencies(input.len(), sample_rate);
+        assert_eq!(output.len(), 129);
+        assert_eq!(frequency.len(), 129);
+
+        let (ipeak, _) = output
+            .iter()
+            .map(|c| c.0.hypot(c.1))
+            .enumerate()
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .unwrap();
+        assert!((frequency[ipeak] - 0.125).abs() < 1e-9);
+        assert!((output[ipeak].0 - 128.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,168 @@
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::units::UnitSystem;
+
+use super::{
+    dispersion::{group_ratio, wavenumber},
+    waves::Error,
+};
+
+const GRAVITY: f64 = 9.81;
+
+/// Relative depth classification of a wave from the ratio of depth to wavelength
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DepthRegime {
+    /// Depth is more than half the wavelength, the bottom has no influence
+    Deep,
+    Intermediate,
+    /// Depth is less than a twentieth of the wavelength, waves are non dispersive
+    Shallow,
+}
+
+impl DepthRegime {
+    pub fn from_relative_depth(relative_depth: f64) -> DepthRegime {
+        if relative_depth > 0.5 {
+            DepthRegime::Deep
+        } else if relative_depth < 0.05 {
+            DepthRegime::Shallow
+        } else {
+            DepthRegime::Intermediate
+        }
+    }
+}
+
+/// Linear (Airy) wave theory properties of a monochromatic wave. Units are metric, gravity is
+/// 9.81 m/s and the density of seawater is 1029 kg/m3
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinearWave {
+    /// Wave period in seconds
+    pub period: f64,
+    /// Wave height in meters
+    pub height: f64,
+    /// Water depth in meters
+    pub depth: f64,
+    /// Wavelength in meters
+    pub wavelength: f64,
+    /// Wavenumber in rad/m
+    pub wavenumber: f64,
+    /// Phase speed in m/s
+    pub celerity: f64,
+    /// Group speed in m/s
+    pub group_velocity: f64,
+    /// Depth averaged energy density in J/m2
+    pub energy: f64,
+    /// Energy flux per unit crest length in W/m
+    pub energy_flux: f64,
+    /// Radiation stress component in the direction of travel in N/m
+    pub radiation_stress_xx: f64,
+    /// Radiation stress component parallel to the crests in N/m
+    pub radiation_stress_yy: f64,
+    /// Amplitude of the horizontal orbital velocity at the bed in m/s
+    pub bottom_orbital_velocity: f64,
+    /// Ursell number H * L^2 / h^3, linear theory is valid well below 26
+    pub ursell: f64,
+    pub regime: DepthRegime,
+}
+
+impl LinearWave {
+    /// Solves the linear dispersion relation for the wave with the given period in seconds,
+    /// height in meters and water depth in meters
+    pub fn new(period: f64, height: f64, depth: f64) -> Result<LinearWave, Error> {
+        if period <= 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "period must be positive, got {period}"
+            )));
+        }
+        if height < 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "wave height must not be negative, got {height}"
+            )));
+        }
+        if depth <= 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "depth must be positive, got {depth}"
+            )));
+        }
+
+        let wavenumber = wavenumber(period, depth).ok_or(Error::ConvergenceFailure)?;
+        let wavelength = 2.0 * PI / wavenumber;
+        let kh = wavenumber * depth;
+
+        let celerity = wavelength / period;
+        let n = group_ratio(kh);
+        let group_velocity = n * celerity;
+
+        let energy = UnitSystem::Metric.density_of_seawater() * GRAVITY * height.powi(2) / 8.0;
+        let bottom_orbital_velocity = if kh > 20.0 {
+            0.0
+        } else {
+            PI * height / (period * kh.sinh())
+        };
+
+        Ok(LinearWave {
+            period,
+            height,
+            depth,
+            wavelength,
+            wavenumber,
+            celerity,
+            group_velocity,
+            energy,
+            energy_flux: energy * group_velocity,
+            radiation_stress_xx: energy * (2.0 * n - 0.5),
+            radiation_stress_yy: energy * (n - 0.5),
+            bottom_orbital_velocity,
+            ursell: height * wavelength.powi(2) / depth.powi(3),
+            regime: DepthRegime::from_relative_depth(depth / wavelength),
+        })
+    }
+
+    /// Angular frequency in rad/s
+    pub fn angular_frequency(&self) -> f64 {
+        2.0 * PI / self.period
+    }
+
+    /// Ratio of group speed to phase speed
+    pub fn group_ratio(&self) -> f64 {
+        self.group_velocity / self.celerity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DepthRegime, LinearWave};
+
+    #[test]
+    fn test_linear_wave_regimes() {
+        // Deep water limits, L0 = g T^2 / 2 pi and cg = c / 2
+        let deep = LinearWave::new(10.0, 1.0, 500.0).unwrap();
+        assert_eq!(deep.regime, DepthRegime::Deep);
+        assert!((deep.wavelength - 156.13).abs() < 0.1);
+        assert!((deep.group_ratio() - 0.5).abs() < 1e-6);
+        assert!(deep.bottom_orbital_velocity < 1e-6);
+        assert!((deep.radiation_stress_xx / deep.energy - 0.5).abs() < 1e-6);
+
+        // Shallow water limits, c = cg = sqrt(g h)
+        let shallow = LinearWave::new(20.0, 0.5, 2.0).unwrap();
+        assert_eq!(shallow.regime, DepthRegime::Shallow);
+        let c = (9.81f64 * 2.0).sqrt();
+        assert!((shallow.celerity - c).abs() / c < 0.01);
+        assert!((shallow.group_ratio() - 1.0).abs() < 0.01);
+        assert!((shallow.radiation_stress_xx / shallow.energy - 1.5).abs() < 0.02);
+        assert!(shallow.ursell > 26.0);
+
+        let intermediate = LinearWave::new(8.0, 1.0, 10.0).unwrap();
+        assert_eq!(intermediate.regime, DepthRegime::Intermediate);
+        assert!(intermediate.celerity < deep.celerity);
+        assert!(
+            (intermediate.energy_flux - intermediate.energy * intermediate.group_velocity).abs()
+                < 1e-9
+        );
+        assert!((intermediate.energy - 1029.0 * 9.81 / 8.0).abs() < 1e-9);
+
+        assert!(LinearWave::new(0.0, 1.0, 10.0).is_err());
+        assert!(LinearWave::new(8.0, 1.0, -1.0).is_err());
+    }
+}
@@ -19,9 +19,47 @@ pub fn is_some_missing(v: f64, missing: f64) -> Option<f64> {
     }
 }
 
+/// Whether a value is missing from NDBC data, which reports missing values as 999.0 or 999.9
+/// while other sources leave them as NaN
+pub fn is_missing_value(v: f64) -> bool {
+    !v.is_finite() || f_eq(v, 999.0) || f_eq(v, 999.9)
+}
+
+/// Ordinary least squares fit of y = slope * x + intercept. Returns the slope, the intercept and
+/// the coefficient of determination, or None when there are fewer than two distinct x values
+pub fn linear_regression(x: &[f64], y: &[f64]) -> Option<(f64, f64, f64)> {
+    let n = x.len().min(y.len());
+    if n < 2 {
+        return None;
+    }
+
+    let x_mean = x[..n].iter().sum::<f64>() / n as f64;
+    let y_mean = y[..n].iter().sum::<f64>() / n as f64;
+
+    let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+    for (xi, yi) in x.iter().zip(y.iter()) {
+        sxx += (xi - x_mean).powi(2);
+        sxy += (xi - x_mean) * (yi - y_mean);
+        syy += (yi - y_mean).powi(2);
+    }
+    if sxx <= 0.0 {
+        return None;
+    }
+
+    let slope = sxy / sxx;
+    let intercept = y_mean - slope * x_mean;
+    let r_squared = if syy > 0.0 {
+        sxy.powi(2) / (sxx * syy)
+    } else {
+        1.0
+    };
+
+    Some((slope, intercept, r_squared))
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{is_some_missing, scalar_from_uv};
+    use super::{is_missing_value, is_some_missing, linear_regression, scalar_from_uv};
 
     #[test]
     fn test_scaler_from_uv() {
@@ -44,5 +82,22 @@ mod tests {
 
         assert!(is_some_missing(v1, MISSING).is_some());
         assert!(is_some_missing(v2, MISSING).is_none());
+
+        assert!(is_missing_value(999.0));
+        assert!(is_missing_value(999.9));
+        assert!(is_missing_value(f64::NAN));
+        assert!(!is_missing_value(v1));
+    }
+
+    #[test]
+    fn test_linear_regression() {
+        let x = [0.0, 1.0, 2.0, 3.0];
+        let y = [1.0, 3.0, 5.0, 7.0];
+        let (slope, intercept, r_squared) = linear_regression(&x, &y).unwrap();
+        assert!((slope - 2.0).abs() < 1e-12);
+        assert!((intercept - 1.0).abs() < 1e-12);
+        assert!((r_squared - 1.0).abs() < 1e-12);
+
+        assert!(linear_regression(&[1.0, 1.0], &[0.0, 1.0]).is_none());
     }
 }
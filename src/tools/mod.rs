@@ -1,10 +1,22 @@
 pub mod analysis;
+pub mod colormap;
 pub mod contour;
 pub mod dap;
 pub mod date;
+pub mod dispersion;
+pub mod fft;
 pub mod interpolation;
+pub mod linear_wave;
 pub mod linspace;
 pub mod math;
+pub mod parametric;
+pub mod partition;
+pub mod render;
 pub mod serde;
+pub mod simulation;
+pub mod spectral_estimation;
+pub mod statistics;
 pub mod vector;
+pub mod wave_growth;
 pub mod waves;
+pub mod zero_crossing;
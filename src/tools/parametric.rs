@@ -0,0 +1,208 @@
+use std::f64::consts::PI;
+
+use crate::tools::{vector::diff, waves::wavenu3};
+
+const GRAVITY: f64 = 9.81;
+
+/// One dimensional spectral shapes used to synthesize a frequency spectrum from bulk parameters
+#[derive(Clone, Debug)]
+pub enum SpectralShape {
+    /// JONSWAP spectrum with the given peak enhancement factor gamma, typically 3.3
+    Jonswap { gamma: f64 },
+    /// Fully developed Pierson-Moskowitz spectrum
+    PiersonMoskowitz,
+    /// Depth limited TMA spectrum (Bouws et al, 1985) with the given peak enhancement
+    /// factor gamma and water depth in meters
+    Tma { gamma: f64, depth: f64 },
+}
+
+impl SpectralShape {
+    /// Unscaled spectral density at the given frequency for a spectrum peaking at fp, both in hz.
+    /// Uses a Phillips constant of 0.0081, the result should be rescaled to the target energy.
+    pub fn density(&self, frequency: f64, fp: f64) -> f64 {
+        if frequency <= 0.0 || fp <= 0.0 {
+            return 0.0;
+        }
+
+        const ALPHA: f64 = 0.0081;
+        let pm = ALPHA
+            * GRAVITY.powi(2)
+            * (2.0 * PI).powi(-4)
+            * frequency.powi(-5)
+            * (-1.25 * (fp / frequency).powi(4)).exp();
+
+        match self {
+            SpectralShape::PiersonMoskowitz => pm,
+            SpectralShape::Jonswap { gamma } => pm * peak_enhancement(*gamma, frequency, fp),
+            SpectralShape::Tma { gamma, depth } => {
+                pm * peak_enhancement(*gamma, frequency, fp) * tma_depth_factor(frequency, *depth)
+            }
+        }
+    }
+}
+
+/// JONSWAP peak enhancement term gamma^r
+fn peak_enhancement(gamma: f64, frequency: f64, fp: f64) -> f64 {
+    let sigma: f64 = if frequency <= fp { 0.07 } else { 0.09 };
+    let r = (-(frequency - fp).powi(2) / (2.0 * sigma.powi(2) * fp.powi(2))).exp();
+    gamma.powf(r)
+}
+
+/// Kitaigorodskii depth transformation factor used by the TMA spectrum
+fn tma_depth_factor(frequency: f64, depth: f64) -> f64 {
+    let (k, _) = wavenu3(2.0 * PI * frequency, depth);
+    let kh = k * depth;
+    if kh > 20.0 {
+        return 1.0;
+    }
+
+    kh.tanh().powi(2) / (1.0 + 2.0 * kh / (2.0 * kh).sinh())
+}
+
+/// Directional spreading functions used to distribute energy across direction
+#[derive(Clone, Debug)]
+pub enum DirectionalSpreading {
+    /// Longuet-Higgins cos-2s spreading, cos(dtheta / 2) ^ 2s
+    CosineSquared { s: f64 },
+    /// Wrapped normal spreading with the given standard deviation in degrees
+    WrappedNormal { sigma: f64 },
+}
+
+impl DirectionalSpreading {
+    /// Unnormalized spreading weight for the given angular distance from the peak direction in radians
+    pub fn weight(&self, dtheta: f64) -> f64 {
+        match self {
+            DirectionalSpreading::CosineSquared { s } => (dtheta / 2.0).cos().abs().powf(2.0 * s),
+            DirectionalSpreading::WrappedNormal { sigma } => {
+                let sigma = sigma.to_radians();
+                (-3..=3)
+                    .map(|k| {
+                        let d = dtheta + 2.0 * PI * k as f64;
+                        (-d.powi(2) / (2.0 * sigma.powi(2))).exp()
+                    })
+                    .sum()
+            }
+        }
+    }
+}
+
+/// Synthesizes a frequency spectrum in m2/hz on the given frequency bins in hz. The result is
+/// scaled so that 4 * sqrt(sum(E * df)) matches the given significant wave height, where df
+/// uses the same bandwidths as `Spectra::dk`
+pub fn frequency_spectrum(shape: &SpectralShape, hs: f64, tp: f64, frequency: &[f64]) -> Vec<f64> {
+    let fp = 1.0 / tp;
+    let df = diff(frequency);
+
+    let density = frequency
+        .iter()
+        .map(|f| shape.density(*f, fp))
+        .collect::<Vec<f64>>();
+
+    let m0 = density
+        .iter()
+        .zip(df.iter())
+        .map(|(e, d)| e * d)
+        .sum::<f64>();
+    if m0 <= 0.0 {
+        return vec![0.0; frequency.len()];
+    }
+
+    let scale = (hs / 4.0).powi(2) / m0;
+    density.iter().map(|e| e * scale).collect()
+}
+
+/// Distributes unit energy across the given direction bins in degrees, with the peak direction
+/// in degrees using the same convention. The result is in 1/rad and integrates to 1 over the
+/// given directional bandwidths in radians
+pub fn directional_distribution(
+    spreading: &DirectionalSpreading,
+    peak_direction: f64,
+    direction: &[f64],
+    dth: &[f64],
+) -> Vec<f64> {
+    let weights = direction
+        .iter()
+        .map(|d| {
+            let dtheta = ((d - peak_direction + 540.0) % 360.0 - 180.0).to_radians();
+            spreading.weight(dtheta)
+        })
+        .collect::<Vec<f64>>();
+
+    let total = weights
+        .iter()
+        .zip(dth.iter())
+        .map(|(w, d)| w * d)
+        .sum::<f64>();
+    if total <= 0.0 {
+        return vec![0.0; direction.len()];
+    }
+
+    weights.iter().map(|w| w / total).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frequency_spectrum_energy() {
+        let frequency = (0..100)
+            .map(|i| 0.035 + i as f64 * 0.005)
+            .collect::<Vec<f64>>();
+        let df = diff(&frequency);
+
+        for shape in [
+            SpectralShape::PiersonMoskowitz,
+            SpectralShape::Jonswap { gamma: 3.3 },
+            SpectralShape::Tma {
+                gamma: 3.3,
+                depth: 10.0,
+            },
+        ] {
+            let e = frequency_spectrum(&shape, 1.5, 10.0, &frequency);
+            let m0 = e.iter().zip(df.iter()).map(|(e, d)| e * d).sum::<f64>();
+            assert!((4.0 * m0.sqrt() - 1.5).abs() < 1e-9);
+
+            let (ip, _) = e
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+                .unwrap();
+            assert!((frequency[ip] - 0.1).abs() < 0.011);
+        }
+    }
+
+    #[test]
+    fn test_jonswap_peakedness() {
+        let pm = SpectralShape::PiersonMoskowitz.density(0.1, 0.1);
+        let jonswap = SpectralShape::Jonswap { gamma: 3.3 }.density(0.1, 0.1);
+        assert!((jonswap / pm - 3.3).abs() < 1e-9);
+
+        // Deep water TMA matches JONSWAP
+        let tma = SpectralShape::Tma {
+            gamma: 3.3,
+            depth: 1000.0,
+        }
+        .density(0.1, 0.1);
+        assert!((tma - jonswap).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_directional_distribution() {
+        let direction = (0..36).map(|i| i as f64 * 10.0).collect::<Vec<f64>>();
+        let dth = vec![10.0f64.to_radians(); 36];
+
+        for spreading in [
+            DirectionalSpreading::CosineSquared { s: 8.0 },
+            DirectionalSpreading::WrappedNormal { sigma: 25.0 },
+        ] {
+            let d = directional_distribution(&spreading, 350.0, &direction, &dth);
+            let total = d.iter().zip(dth.iter()).map(|(d, dd)| d * dd).sum::<f64>();
+            assert!((total - 1.0).abs() < 1e-9);
+
+            // Peak wraps around north
+            assert!(d[35] > d[0] && d[0] > d[1]);
+            assert!((d[34] - d[0]).abs() < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,527 @@
+use serde::{Deserialize, Serialize};
+
+use crate::tools::analysis::{nearest_neighbors, smoothed_watershed, WatershedError};
+
+/// Temporary label for bins classified as wind sea, replaced with partition 1 once all of the
+/// swell partitions are known
+const WIND_SEA: i32 = i32::MAX;
+
+/// Methods used to separate swell and wind sea partitions, following the PTM options in WW3
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum PartitionMethod {
+    /// Watershed partitioning, where every partition with a wind sea fraction above the wind sea
+    /// cutoff is combined into a single wind sea partition
+    #[default]
+    Ptm1,
+    /// Watershed partitioning, where all of the wind forced energy is split off from the swell
+    /// partitions into a single wind sea partition
+    Ptm2,
+    /// Watershed partitioning only, without classifying wind sea
+    Ptm3,
+    /// Wave age cutoff only, giving a single wind sea partition and a single swell partition
+    Ptm4,
+    /// Frequency cutoff in hz only, giving a single high frequency partition and a single low
+    /// frequency partition
+    Ptm5 { cutoff: f64 },
+}
+
+/// Options for partitioning spectral energy into discrete swell components
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PartitionConfig {
+    pub method: PartitionMethod,
+    /// Number of levels the energy is digitized into for the watershed
+    pub levels: usize,
+    /// Standard deviation in bins of the gaussian smoothing applied before the watershed
+    pub smoothing: Option<f64>,
+    /// Partitions holding less than this fraction of the total energy are merged into their
+    /// neighbors
+    pub min_energy_fraction: f64,
+    /// Partitions with a significant wave height in meters below this are merged into their
+    /// neighbors
+    pub min_hs: f64,
+    /// Partitions are merged when their peak frequencies differ by less than this fraction of
+    /// the higher peak frequency and their peak directions are within the direction tolerance
+    pub merge_frequency_tolerance: f64,
+    /// Peak direction tolerance in degrees used when merging partitions, zero disables merging
+    pub merge_direction_tolerance: f64,
+    /// Wind sea fraction above which a partition is classified as wind sea with PTM1
+    pub wind_sea_cutoff: f64,
+}
+
+impl Default for PartitionConfig {
+    fn default() -> Self {
+        PartitionConfig {
+            method: PartitionMethod::Ptm1,
+            levels: 100,
+            smoothing: None,
+            min_energy_fraction: 0.0,
+            min_hs: 0.0,
+            merge_frequency_tolerance: 0.0,
+            merge_direction_tolerance: 0.0,
+            wind_sea_cutoff: 0.333,
+        }
+    }
+}
+
+impl PartitionConfig {
+    /// Plain watershed partitioning with the given levels and smoothing
+    pub fn watershed(levels: usize, smoothing: Option<f64>) -> Self {
+        PartitionConfig {
+            method: PartitionMethod::Ptm3,
+            levels,
+            smoothing,
+            ..Default::default()
+        }
+    }
+}
+
+/// Partitions spectral energy laid out as `energy[ik + ith * nk]`, with the direction in degrees
+/// and the bin widths `dk` in hz and `dth` in radians. The wind sea mask flags the wind forced
+/// bins and is required to classify wind sea with PTM1, PTM2 and PTM4.
+///
+/// Returns the partition label for every bin along with the partition count, including the
+/// zero partition. When wind sea is classified it is always partition 1, as is the high
+/// frequency partition with PTM5
+pub fn partition_spectrum(
+    energy: &[f64],
+    frequency: &[f64],
+    direction: &[f64],
+    dk: &[f64],
+    dth: &[f64],
+    wind_sea_mask: Option<&[bool]>,
+    config: &PartitionConfig,
+) -> Result<(Vec<i32>, usize), WatershedError> {
+    let nk = frequency.len();
+    let nth = direction.len();
+    if energy.len() != nk * nth || dk.len() != nk || dth.len() != nth {
+        return Err(WatershedError::InvalidData);
+    }
+    if let Some(mask) = wind_sea_mask {
+        if mask.len() != energy.len() {
+            return Err(WatershedError::InvalidData);
+        }
+    }
+
+    let weights = (0..energy.len())
+        .map(|i| dk[i % nk] * dth[i / nk])
+        .collect::<Vec<f64>>();
+
+    let labels = match (&config.method, wind_sea_mask) {
+        (PartitionMethod::Ptm4, Some(mask)) => {
+            mask.iter().map(|w| if *w { WIND_SEA } else { 2 }).collect()
+        }
+        (PartitionMethod::Ptm4, None) => vec![2; energy.len()],
+        (PartitionMethod::Ptm5 { cutoff }, _) => (0..energy.len())
+            .map(|i| {
+                if frequency[i % nk] >= *cutoff {
+                    WIND_SEA
+                } else {
+                    2
+                }
+            })
+            .collect(),
+        (method, mask) => {
+            let (mut labels, _) =
+                smoothed_watershed(energy, nk, nth, config.levels, config.smoothing)?;
+
+            if config.merge_frequency_tolerance > 0.0 && config.merge_direction_tolerance > 0.0 {
+                merge_close_partitions(
+                    &mut labels,
+                    energy,
+                    frequency,
+                    direction,
+                    config.merge_frequency_tolerance,
+                    config.merge_direction_tolerance,
+                );
+            }
+
+            if config.min_energy_fraction > 0.0 || config.min_hs > 0.0 {
+                merge_small_partitions(
+                    &mut labels,
+                    energy,
+                    &weights,
+                    nk,
+                    nth,
+                    config.min_energy_fraction,
+                    config.min_hs,
+                );
+            }
+
+            match (method, mask) {
+                (PartitionMethod::Ptm1, Some(mask)) => {
+                    classify_wind_sea_partitions(
+                        &mut labels,
+                        energy,
+                        &weights,
+                        mask,
+                        config.wind_sea_cutoff,
+                    );
+                }
+                (PartitionMethod::Ptm2, Some(mask)) => {
+                    labels
+                        .iter_mut()
+                        .zip(mask.iter())
+                        .filter(|(_, w)| **w)
+                        .for_each(|(l, _)| *l = WIND_SEA);
+                }
+                _ => {}
+            }
+
+            labels
+        }
+    };
+
+    let reserve_wind_sea = match config.method {
+        PartitionMethod::Ptm1 | PartitionMethod::Ptm2 => wind_sea_mask.is_some(),
+        PartitionMethod::Ptm3 => false,
+        PartitionMethod::Ptm4 | PartitionMethod::Ptm5 { .. } => true,
+    };
+
+    Ok(relabel(labels, reserve_wind_sea))
+}
+
+/// Total energy and index of the peak bin for every partition label
+fn partition_stats(labels: &[i32], energy: &[f64], weights: &[f64]) -> Vec<(f64, Option<usize>)> {
+    let count = labels
+        .iter()
+        .copied()
+        .filter(|l| *l != WIND_SEA)
+        .max()
+        .unwrap_or(0)
+        .max(0) as usize;
+    let mut stats: Vec<(f64, Option<usize>)> = vec![(0.0, None); count + 1];
+
+    for (i, label) in labels.iter().enumerate() {
+        if *label < 1 || *label == WIND_SEA {
+            continue;
+        }
+
+        let stat = &mut stats[*label as usize];
+        stat.0 += energy[i] * weights[i];
+        match stat.1 {
+            Some(peak) if energy[peak] >= energy[i] => {}
+            _ => stat.1 = Some(i),
+        }
+    }
+
+    stats
+}
+
+fn replace_label(labels: &mut [i32], from: i32, to: i32) {
+    labels
+        .iter_mut()
+        .filter(|l| **l == from)
+        .for_each(|l| *l = to);
+}
+
+/// Merges partitions whose spectral peaks are too close in both frequency and direction into
+/// the more energetic of the two
+fn merge_close_partitions(
+    labels: &mut [i32],
+    energy: &[f64],
+    frequency: &[f64],
+    direction: &[f64],
+    frequency_tolerance: f64,
+    direction_tolerance: f64,
+) {
+    let nk = frequency.len();
+    let peaks = partition_stats(labels, energy, &vec![1.0; energy.len()])
+        .iter()
+        .enumerate()
+        .filter_map(|(label, (_, peak))| peak.map(|p| (label as i32, p)))
+        .collect::<Vec<(i32, usize)>>();
+
+    // Visit partitions from the highest peak down, so smaller partitions are absorbed by the
+    // largest one they are close to
+    let mut order = peaks.clone();
+    order.sort_by(|a, b| energy[b.1].partial_cmp(&energy[a.1]).unwrap());
+
+    let mut target = peaks
+        .iter()
+        .map(|(label, _)| (*label, *label))
+        .collect::<std::collections::HashMap<i32, i32>>();
+
+    for (i, (label, peak)) in order.iter().enumerate() {
+        let (fp, dp) = (frequency[peak % nk], direction[peak / nk]);
+        for (other_label, other_peak) in order.iter().take(i) {
+            let (ofp, odp) = (frequency[other_peak % nk], direction[other_peak / nk]);
+            let df = (fp - ofp).abs() / fp.max(ofp);
+            let dd = ((dp - odp + 540.0) % 360.0 - 180.0).abs();
+            if df <= frequency_tolerance && dd <= direction_tolerance {
+                target.insert(*label, target[other_label]);
+                break;
+            }
+        }
+    }
+
+    for l in labels.iter_mut() {
+        if let Some(t) = target.get(l) {
+            *l = *t;
+        }
+    }
+}
+
+/// Merges partitions below the energy or wave height thresholds into the neighboring partition
+/// they share the longest boundary with, or drops them to the zero partition when isolated
+fn merge_small_partitions(
+    labels: &mut [i32],
+    energy: &[f64],
+    weights: &[f64],
+    width: usize,
+    height: usize,
+    min_energy_fraction: f64,
+    min_hs: f64,
+) {
+    let total = energy
+        .iter()
+        .zip(weights.iter())
+        .map(|(e, w)| e * w)
+        .sum::<f64>();
+    if total <= 0.0 {
+        return;
+    }
+
+    let neighbors = (0..labels.len())
+        .map(|i| nearest_neighbors(width, height, i))
+        .collect::<Vec<Vec<usize>>>();
+
+    loop {
+        let smallest = partition_stats(labels, energy, weights)
+            .iter()
+            .enumerate()
+            .filter(|(_, (m0, peak))| {
+                peak.is_some()
+                    && (m0 / total < min_energy_fraction || 4.0 * m0.max(0.0).sqrt() < min_hs)
+            })
+            .min_by(|a, b| a.1 .0.partial_cmp(&b.1 .0).unwrap())
+            .map(|(label, _)| label as i32);
+
+        let Some(label) = smallest else {
+            break;
+        };
+
+        let mut boundary: Vec<(i32, usize)> = Vec::new();
+        for (i, l) in labels.iter().enumerate() {
+            if *l != label {
+                continue;
+            }
+            for n in &neighbors[i] {
+                let nl = labels[*n];
+                if nl < 1 || nl == label {
+                    continue;
+                }
+                match boundary.iter_mut().find(|(b, _)| *b == nl) {
+                    Some((_, c)) => *c += 1,
+                    None => boundary.push((nl, 1)),
+                }
+            }
+        }
+
+        let merged = boundary
+            .iter()
+            .max_by_key(|(_, c)| *c)
+            .map(|(l, _)| *l)
+            .unwrap_or(0);
+        replace_label(labels, label, merged);
+    }
+}
+
+/// Flags every partition whose fraction of wind forced energy is above the cutoff as wind sea
+fn classify_wind_sea_partitions(
+    labels: &mut [i32],
+    energy: &[f64],
+    weights: &[f64],
+    wind_sea_mask: &[bool],
+    cutoff: f64,
+) {
+    let stats = partition_stats(labels, energy, weights);
+    let mut wind_energy = vec![0.0; stats.len()];
+    for (i, label) in labels.iter().enumerate() {
+        if *label > 0 && wind_sea_mask[i] {
+            wind_energy[*label as usize] += energy[i] * weights[i];
+        }
+    }
+
+    for (label, (m0, _)) in stats.iter().enumerate() {
+        if *m0 > 0.0 && wind_energy[label] / m0 > cutoff {
+            replace_label(labels, label as i32, WIND_SEA);
+        }
+    }
+}
+
+/// Renumbers the partitions consecutively, with wind sea as partition 1 when reserved
+fn relabel(mut labels: Vec<i32>, reserve_wind_sea: bool) -> (Vec<i32>, usize) {
+    let mut existing = labels
+        .iter()
+        .copied()
+        .filter(|l| *l > 0 && *l != WIND_SEA)
+        .collect::<Vec<i32>>();
+    existing.sort_unstable();
+    existing.dedup();
+
+    let offset: usize = if reserve_wind_sea { 2 } else { 1 };
+    for l in labels.iter_mut() {
+        if *l == WIND_SEA {
+            *l = 1;
+        } else if *l > 0 {
+            *l = (existing.binary_search(l).unwrap() + offset) as i32;
+        }
+    }
+
+    (labels, existing.len() + offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NK: usize = 30;
+    const NTH: usize = 24;
+
+    fn grid() -> (Vec<f64>, Vec<f64>, Vec<f64>, Vec<f64>) {
+        let frequency = (0..NK)
+            .map(|i| 0.04 + i as f64 * 0.01)
+            .collect::<Vec<f64>>();
+        let direction = (0..NTH).map(|i| i as f64 * 15.0).collect::<Vec<f64>>();
+        (
+            frequency,
+            direction,
+            vec![0.01; NK],
+            vec![15.0f64.to_radians(); NTH],
+        )
+    }
+
+    fn peaks(frequency: &[f64], direction: &[f64], peaks: &[(f64, f64, f64)]) -> Vec<f64> {
+        (0..NK * NTH)
+            .map(|i| {
+                let (f, d) = (frequency[i % NK], direction[i / NK]);
+                peaks
+                    .iter()
+                    .map(|(a, fp, dp)| {
+                        let dd = (d - dp + 540.0) % 360.0 - 180.0;
+                        a * (-((f - fp) / 0.015).powi(2) - (dd / 25.0).powi(2)).exp()
+                    })
+                    .sum::<f64>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_watershed_only() {
+        let (frequency, direction, dk, dth) = grid();
+        let energy = peaks(
+            &frequency,
+            &direction,
+            &[(2.0, 0.08, 90.0), (1.0, 0.2, 270.0)],
+        );
+
+        // With many levels the tails split off into tiny partitions
+        let config = PartitionConfig::watershed(500, Some(0.5));
+        let (_, count) =
+            partition_spectrum(&energy, &frequency, &direction, &dk, &dth, None, &config).unwrap();
+        assert!(count > 3);
+
+        let config = PartitionConfig {
+            min_energy_fraction: 0.01,
+            ..config
+        };
+        let (labels, count) =
+            partition_spectrum(&energy, &frequency, &direction, &dk, &dth, None, &config).unwrap();
+        assert_eq!(count, 3);
+        assert_ne!(labels[4 + 6 * NK], labels[16 + 18 * NK]);
+    }
+
+    #[test]
+    fn test_merge_partitions() {
+        let (frequency, direction, dk, dth) = grid();
+        let energy = peaks(
+            &frequency,
+            &direction,
+            &[(2.0, 0.08, 90.0), (1.0, 0.11, 135.0), (0.01, 0.25, 270.0)],
+        );
+
+        let config = PartitionConfig::watershed(100, None);
+        let (_, count) =
+            partition_spectrum(&energy, &frequency, &direction, &dk, &dth, None, &config).unwrap();
+        assert_eq!(count, 4);
+
+        let config = PartitionConfig {
+            merge_frequency_tolerance: 0.3,
+            merge_direction_tolerance: 50.0,
+            ..config
+        };
+        let (labels, count) =
+            partition_spectrum(&energy, &frequency, &direction, &dk, &dth, None, &config).unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(labels[4 + 6 * NK], labels[7 + 9 * NK]);
+
+        let config = PartitionConfig {
+            min_hs: 0.1,
+            ..config
+        };
+        let (labels, count) =
+            partition_spectrum(&energy, &frequency, &direction, &dk, &dth, None, &config).unwrap();
+        assert_eq!(count, 2);
+        assert!(labels.iter().all(|l| *l == 1));
+    }
+
+    #[test]
+    fn test_wind_sea_methods() {
+        let (frequency, direction, dk, dth) = grid();
+        let energy = peaks(
+            &frequency,
+            &direction,
+            &[(2.0, 0.08, 90.0), (1.0, 0.2, 270.0)],
+        );
+        let mask = (0..NK * NTH)
+            .map(|i| frequency[i % NK] > 0.15 && (direction[i / NK] - 270.0).abs() < 90.0)
+            .collect::<Vec<bool>>();
+
+        for method in [PartitionMethod::Ptm1, PartitionMethod::Ptm2] {
+            let config = PartitionConfig {
+                method,
+                ..Default::default()
+            };
+            let (labels, count) = partition_spectrum(
+                &energy,
+                &frequency,
+                &direction,
+                &dk,
+                &dth,
+                Some(&mask),
+                &config,
+            )
+            .unwrap();
+            assert!(count >= 3);
+            assert_eq!(labels[16 + 18 * NK], 1);
+            assert_eq!(labels[4 + 6 * NK], 2);
+        }
+
+        let config = PartitionConfig {
+            method: PartitionMethod::Ptm4,
+            ..Default::default()
+        };
+        let (labels, count) = partition_spectrum(
+            &energy,
+            &frequency,
+            &direction,
+            &dk,
+            &dth,
+            Some(&mask),
+            &config,
+        )
+        .unwrap();
+        assert_eq!(count, 3);
+        assert!(labels.iter().zip(mask.iter()).all(|(l, w)| (*l == 1) == *w));
+
+        let config = PartitionConfig {
+            method: PartitionMethod::Ptm5 { cutoff: 0.095 },
+            ..Default::default()
+        };
+        let (labels, count) =
+            partition_spectrum(&energy, &frequency, &direction, &dk, &dth, None, &config).unwrap();
+        assert_eq!(count, 3);
+        assert_eq!(labels[5], 2);
+        assert_eq!(labels[6], 1);
+    }
+}
@@ -0,0 +1,350 @@
+use std::io::Cursor;
+
+use image::{ImageOutputFormat, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::spectra::Spectra;
+
+use super::colormap::Colormap;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum RenderError {
+    InvalidData,
+    EncodingFailure(String),
+}
+
+impl std::fmt::Display for RenderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RenderError::InvalidData => write!(f, "Invalid data encountered while rendering"),
+            RenderError::EncodingFailure(e) => write!(f, "Failed to encode image: {e}"),
+        }
+    }
+}
+
+/// How energy values are mapped onto the colormap
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum EnergyScale {
+    /// Energy relative to the maximum energy
+    Linear,
+    /// Energy in decades below the maximum energy, energy more than the given
+    /// number of decades below the maximum is drawn at the bottom of the colormap
+    Log { decades: f64 },
+}
+
+/// Options for rendering a polar plot of a spectra
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PolarPlotOptions {
+    /// Width and height of the image in pixels
+    pub size: usize,
+    pub colormap: Colormap,
+    pub scale: EnergyScale,
+    /// The maximum period to draw, defaults to the longest period of the spectra
+    pub period_threshold: Option<f64>,
+    /// The exponent used to scale the period to the radius
+    pub exp_scale: Option<f64>,
+    /// Periods in seconds to draw rings at
+    pub period_rings: Vec<f64>,
+    /// Draw the N, E, S, W compass labels
+    pub compass_labels: bool,
+    /// Color of everything outside of the plot circle
+    pub background: [u8; 4],
+    /// Color of the period rings, labels and partition outlines
+    pub foreground: [u8; 4],
+}
+
+impl Default for PolarPlotOptions {
+    fn default() -> Self {
+        PolarPlotOptions {
+            size: 512,
+            colormap: Colormap::default(),
+            scale: EnergyScale::Linear,
+            period_threshold: None,
+            exp_scale: None,
+            period_rings: vec![5.0, 10.0, 15.0, 20.0],
+            compass_labels: true,
+            background: [0, 0, 0, 0],
+            foreground: [255, 255, 255, 200],
+        }
+    }
+}
+
+/// 5x7 bitmap glyphs for the characters used in the plot labels
+fn glyph(c: char) -> Option<[u8; 7]> {
+    let rows = match c {
+        'N' => [0x11, 0x19, 0x15, 0x13, 0x11, 0x11, 0x11],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        's' => [0x00, 0x00, 0x0E, 0x10, 0x0E, 0x01, 0x1E],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        _ => return None,
+    };
+    Some(rows)
+}
+
+/// Alpha blends the given color over the pixel
+fn blend(image: &mut RgbaImage, x: i64, y: i64, color: [u8; 4]) {
+    if x < 0 || y < 0 || x >= image.width() as i64 || y >= image.height() as i64 {
+        return;
+    }
+
+    let pixel = image.get_pixel_mut(x as u32, y as u32);
+    let alpha = color[3] as f64 / 255.0;
+    for i in 0..3 {
+        pixel[i] = (color[i] as f64 * alpha + pixel[i] as f64 * (1.0 - alpha)).round() as u8;
+    }
+    pixel[3] = pixel[3].max(color[3]);
+}
+
+/// Draws the text centered on the given pixel
+fn draw_text(image: &mut RgbaImage, text: &str, center: (f64, f64), scale: i64, color: [u8; 4]) {
+    let glyph_width = 6 * scale;
+    let x0 = center.0.round() as i64 - (text.len() as i64 * glyph_width) / 2;
+    let y0 = center.1.round() as i64 - (7 * scale) / 2;
+
+    for (i, c) in text.chars().enumerate() {
+        let Some(rows) = glyph(c) else {
+            continue;
+        };
+
+        let gx = x0 + i as i64 * glyph_width;
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..5 {
+                if bits & (0x10 >> col) == 0 {
+                    continue;
+                }
+
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        blend(
+                            image,
+                            gx + col * scale + dx,
+                            y0 + row as i64 * scale + dy,
+                            color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Renders the spectra as a polar plot to an rgba png image. Direction is the direction the
+/// energy is coming from with north up, and period increases with the radius. When a partition
+/// map from `Spectra::partition` is given the outlines of every partition are drawn over the energy
+pub fn render_polar_png(
+    spectra: &Spectra,
+    options: &PolarPlotOptions,
+    partitions: Option<&[i32]>,
+) -> Result<Vec<u8>, RenderError> {
+    let size = options.size;
+    if size == 0 || spectra.energy.is_empty() {
+        return Err(RenderError::InvalidData);
+    }
+
+    if partitions.is_some_and(|p| p.len() != spectra.energy.len()) {
+        return Err(RenderError::InvalidData);
+    }
+
+    let map =
+        spectra.compute_cartesian_projection_map(size, options.period_threshold, options.exp_scale);
+    let projected = spectra.project_cartesian_with_map(&spectra.energy, &map);
+
+    let (_, max_energy) = spectra.energy_range();
+    let normalized = projected
+        .iter()
+        .map(|e| {
+            if max_energy <= 0.0 || e.is_nan() || *e <= 0.0 {
+                return 0.0;
+            }
+
+            match options.scale {
+                EnergyScale::Linear => e / max_energy,
+                EnergyScale::Log { decades } => 1.0 + (e / max_energy).log10() / decades.max(0.1),
+            }
+        })
+        .collect::<Vec<f64>>();
+
+    let center = size as f64 / 2.0;
+    let radius = size as f64 / 2.0;
+    let inside = |x: usize, y: usize| {
+        let dx = x as f64 - center;
+        let dy = y as f64 - center;
+        (dx * dx + dy * dy).sqrt() <= radius
+    };
+
+    let mut image = RgbaImage::from_pixel(size as u32, size as u32, Rgba(options.background));
+    for y in 0..size {
+        for x in 0..size {
+            if !inside(x, y) {
+                continue;
+            }
+
+            let [r, g, b] = options.colormap.color(normalized[x + y * size]);
+            image.put_pixel(x as u32, y as u32, Rgba([r, g, b, 255]));
+        }
+    }
+
+    // Partition outlines are drawn wherever neighboring pixels belong to different partitions
+    if let Some(partitions) = partitions {
+        let partition_map = partitions.iter().map(|p| *p as f64).collect::<Vec<f64>>();
+        let projected_partitions = spectra.project_cartesian_with_map(&partition_map, &map);
+
+        for y in 0..size {
+            for x in 0..size {
+                if !inside(x, y) {
+                    continue;
+                }
+
+                let id = projected_partitions[x + y * size];
+                let is_edge = [(1, 0), (0, 1)].iter().any(|(dx, dy)| {
+                    let (nx, ny) = (x + dx, y + dy);
+                    nx < size
+                        && ny < size
+                        && inside(nx, ny)
+                        && projected_partitions[nx + ny * size] != id
+                });
+
+                if is_edge {
+                    blend(&mut image, x as i64, y as i64, options.foreground);
+                }
+            }
+        }
+    }
+
+    let text_scale = (size as i64 / 256).max(1);
+
+    // Period rings use the same radial scaling as the cartesian projection
+    let exp_scale = options.exp_scale.unwrap_or(1.0);
+    let max_period = spectra.period().iter().cloned().fold(f64::NAN, f64::max);
+    let period_threshold = options.period_threshold.unwrap_or(max_period);
+    for period in options
+        .period_rings
+        .iter()
+        .filter(|p| **p > 0.0 && **p <= period_threshold)
+    {
+        let r = radius * period.powf(exp_scale) / period_threshold.powf(exp_scale);
+        let steps = (2.0 * std::f64::consts::PI * r).ceil().max(8.0) as usize * 2;
+        for i in 0..steps {
+            let t = i as f64 / steps as f64 * 2.0 * std::f64::consts::PI;
+            blend(
+                &mut image,
+                (center + r * t.cos()).round() as i64,
+                (center + r * t.sin()).round() as i64,
+                options.foreground,
+            );
+        }
+
+        let label_angle = (-45.0f64).to_radians();
+        draw_text(
+            &mut image,
+            &format!("{}s", period.round() as i64),
+            (
+                center + r * label_angle.cos(),
+                center + r * label_angle.sin(),
+            ),
+            text_scale,
+            options.foreground,
+        );
+    }
+
+    if options.compass_labels {
+        let offset = radius - 6.0 * text_scale as f64;
+        for (label, (dx, dy)) in [
+            ("N", (0.0, -1.0)),
+            ("E", (1.0, 0.0)),
+            ("S", (0.0, 1.0)),
+            ("W", (-1.0, 0.0)),
+        ] {
+            draw_text(
+                &mut image,
+                label,
+                (center + dx * offset, center + dy * offset),
+                text_scale,
+                options.foreground,
+            );
+        }
+    }
+
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+        .map_err(|e| RenderError::EncodingFailure(e.to_string()))?;
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        spectra::Spectra,
+        tools::parametric::{DirectionalSpreading, SpectralShape},
+    };
+
+    use super::{render_polar_png, EnergyScale, PolarPlotOptions};
+
+    #[test]
+    fn test_render_polar_png() {
+        let frequency = (0..40).map(|i| 0.035 * 1.07f64.powi(i)).collect::<Vec<_>>();
+        let direction = (0..36).map(|i| i as f64 * 10.0).collect::<Vec<_>>();
+
+        // Swell coming from the north
+        let spectra = Spectra::from_parametric(
+            &frequency,
+            &direction,
+            2.0,
+            12.0,
+            0.0,
+            &SpectralShape::Jonswap { gamma: 3.3 },
+            &DirectionalSpreading::CosineSquared { s: 20.0 },
+        );
+
+        let options = PolarPlotOptions {
+            size: 128,
+            period_threshold: Some(20.0),
+            compass_labels: false,
+            period_rings: vec![],
+            ..Default::default()
+        };
+
+        let png = render_polar_png(&spectra, &options, None).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (128, 128));
+
+        // Outside the plot circle is transparent
+        assert_eq!(image.get_pixel(0, 0)[3], 0);
+
+        // The 12 second peak is drawn north of the center, not south
+        let r = (64.0 * 12.0 / 20.0) as u32;
+        let north = options.colormap.color(1.0);
+        let north_pixel = image.get_pixel(64, 64 - r);
+        let south_pixel = image.get_pixel(64, 64 + r);
+        let distance = |p: &image::Rgba<u8>| {
+            (0..3)
+                .map(|i| (p[i] as i32 - north[i] as i32).abs())
+                .sum::<i32>()
+        };
+        assert!(distance(north_pixel) < distance(south_pixel));
+
+        let log_options = PolarPlotOptions {
+            size: 128,
+            scale: EnergyScale::Log { decades: 3.0 },
+            ..Default::default()
+        };
+        let partitions = spectra.partition(100, None).unwrap();
+        let png = render_polar_png(&spectra, &log_options, Some(&partitions.0)).unwrap();
+        assert!(image::load_from_memory(&png).is_ok());
+
+        assert!(render_polar_png(&spectra, &options, Some(&[0, 1])).is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use std::f64::consts::PI;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Synthesises a sea surface elevation time series in meters from a one dimensional frequency
+/// spectrum in m2/hz using the random phase method. Each frequency bin contributes a
+/// sinusoid with amplitude sqrt(2 * S(f) * df) and a uniformly random phase. The frequencies
+/// are also jittered randomly within their bins so the series does not repeat every 1 / df
+/// seconds. Samples are spaced dt seconds apart over the given duration in seconds. Passing a
+/// seed gives a reproducible series
+pub fn random_phase_surface(
+    frequency: &[f64],
+    energy: &[f64],
+    df: &[f64],
+    duration: f64,
+    dt: f64,
+    seed: Option<u64>,
+) -> Vec<f64> {
+    if dt <= 0.0 || duration <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let components = frequency
+        .iter()
+        .zip(energy.iter())
+        .zip(df.iter())
+        .filter(|((_, e), _)| **e > 0.0)
+        .map(|((f, e), d)| {
+            let amplitude = (2.0 * e * d).sqrt();
+            let jitter = rng.gen_range(-0.5..0.5) * d;
+            let phase = rng.gen_range(0.0..2.0 * PI);
+            (amplitude, 2.0 * PI * (f + jitter), phase)
+        })
+        .collect::<Vec<_>>();
+
+    let count = (duration / dt).floor() as usize + 1;
+    (0..count)
+        .map(|i| {
+            let t = i as f64 * dt;
+            components
+                .iter()
+                .map(|(a, omega, phase)| a * (omega * t + phase).cos())
+                .sum()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::random_phase_surface;
+    use crate::tools::{
+        parametric::SpectralShape,
+        statistics::{spectral_moment, test_frequency_spectrum},
+    };
+
+    #[test]
+    fn test_random_phase_surface() {
+        let (frequency, energy, df) =
+            test_frequency_spectrum(&SpectralShape::Jonswap { gamma: 3.3 }, 200);
+
+        let a = random_phase_surface(&frequency, &energy, &df, 3600.0, 0.5, Some(42));
+        let b = random_phase_surface(&frequency, &energy, &df, 3600.0, 0.5, Some(42));
+        let c = random_phase_surface(&frequency, &energy, &df, 3600.0, 0.5, Some(7));
+        assert_eq!(a.len(), 7201);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        // The variance of the surface matches the zeroth moment of the spectrum
+        let m0 = spectral_moment(&frequency, &energy, &df, 0);
+        let mean = a.iter().sum::<f64>() / a.len() as f64;
+        let variance = a.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / a.len() as f64;
+        assert!((variance / m0 - 1.0).abs() < 0.15);
+
+        assert!(random_phase_surface(&frequency, &energy, &df, 3600.0, 0.0, None).is_empty());
+    }
+}
@@ -0,0 +1,578 @@
+use std::{f64::consts::PI, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    fft::{rfft, rfft_frequencies},
+    math::is_missing_value,
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SpectralEstimationError {
+    MismatchedLengths,
+    InvalidSampleRate,
+    InvalidSegmentLength(usize),
+    InsufficientSamples { required: usize, available: usize },
+}
+
+impl fmt::Display for SpectralEstimationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpectralEstimationError::MismatchedLengths => {
+                write!(f, "all input series must have the same length")
+            }
+            SpectralEstimationError::InvalidSampleRate => {
+                write!(f, "sample rate must be positive")
+            }
+            SpectralEstimationError::InvalidSegmentLength(n) => {
+                write!(f, "invalid segment length {n}, must be at least 2")
+            }
+            SpectralEstimationError::InsufficientSamples {
+                required,
+                available,
+            } => write!(
+                f,
+                "at least {required} samples are required but only {available} are available"
+            ),
+        }
+    }
+}
+
+/// Cross spectral densities indexed [i][j][frequency] as (re, im) pairs
+type CrossSpectralMatrix = Vec<Vec<Vec<(f64, f64)>>>;
+
+/// Welch cross spectral density matrix between every pair of the given series, averaged over
+/// Hann windowed segments of the given length overlapping by half. Each segment has its mean
+/// removed. Returns the positive frequencies in hz and the one sided densities indexed
+/// [i][j][frequency] as (re, im) pairs of conj(X_i) * X_j, in units of the series squared per hz
+fn welch_matrix(
+    series: &[&[f64]],
+    sample_rate: f64,
+    segment_length: usize,
+) -> Result<(Vec<f64>, CrossSpectralMatrix), SpectralEstimationError> {
+    if sample_rate <= 0.0 {
+        return Err(SpectralEstimationError::InvalidSampleRate);
+    }
+    if segment_length < 2 {
+        return Err(SpectralEstimationError::InvalidSegmentLength(
+            segment_length,
+        ));
+    }
+
+    let length = series.first().map(|s| s.len()).unwrap_or(0);
+    if series.iter().any(|s| s.len() != length) {
+        return Err(SpectralEstimationError::MismatchedLengths);
+    }
+    if length < segment_length {
+        return Err(SpectralEstimationError::InsufficientSamples {
+            required: segment_length,
+            available: length,
+        });
+    }
+
+    let window = (0..segment_length)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f64 / segment_length as f64).cos())
+        .collect::<Vec<f64>>();
+    let window_power = window.iter().map(|w| w.powi(2)).sum::<f64>();
+
+    let step = (segment_length / 2).max(1);
+    let segment_count = (length - segment_length) / step + 1;
+    let frequency = rfft_frequencies(segment_length, sample_rate);
+    let nf = frequency.len();
+
+    let mut matrix = vec![vec![vec![(0.0, 0.0); nf]; series.len()]; series.len()];
+    for segment in 0..segment_count {
+        let start = segment * step;
+        let transforms = series
+            .iter()
+            .map(|s| {
+                let data = &s[start..start + segment_length];
+                let mean = data.iter().sum::<f64>() / segment_length as f64;
+                let windowed = data
+                    .iter()
+                    .zip(window.iter())
+                    .map(|(v, w)| (v - mean) * w)
+                    .collect::<Vec<f64>>();
+                rfft(&windowed)
+            })
+            .collect::<Vec<_>>();
+
+        for (i, xi) in transforms.iter().enumerate() {
+            for (j, xj) in transforms.iter().enumerate() {
+                for k in 0..nf {
+                    let (a, b) = (xi[k], xj[k]);
+                    let cross = (a.0 * b.0 + a.1 * b.1, a.0 * b.1 - a.1 * b.0);
+                    matrix[i][j][k].0 += cross.0;
+                    matrix[i][j][k].1 += cross.1;
+                }
+            }
+        }
+    }
+
+    // One sided scaling, the zero and nyquist terms are not doubled
+    let scale = 1.0 / (sample_rate * window_power * segment_count as f64);
+    for row in matrix.iter_mut() {
+        for column in row.iter_mut() {
+            for (k, value) in column.iter_mut().enumerate() {
+                let one_sided = if k == 0 || 2 * k == segment_length {
+                    1.0
+                } else {
+                    2.0
+                };
+                value.0 *= scale * one_sided;
+                value.1 *= scale * one_sided;
+            }
+        }
+    }
+
+    // The zero frequency term is meaningless after removing the mean
+    let frequency = frequency[1..].to_vec();
+    for row in matrix.iter_mut() {
+        for column in row.iter_mut() {
+            column.remove(0);
+        }
+    }
+
+    Ok((frequency, matrix))
+}
+
+/// Welch power spectral density of a series sampled at the given rate in hz, using Hann
+/// windowed segments of the given length overlapping by half. Returns the positive
+/// frequencies in hz and the one sided density in units of the series squared per hz
+pub fn welch_power_spectral_density(
+    x: &[f64],
+    sample_rate: f64,
+    segment_length: usize,
+) -> Result<(Vec<f64>, Vec<f64>), SpectralEstimationError> {
+    let (frequency, matrix) = welch_matrix(&[x], sample_rate, segment_length)?;
+    let density = matrix[0][0].iter().map(|c| c.0).collect();
+    Ok((frequency, density))
+}
+
+/// One sided cross spectral density conj(X) * Y between two series
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CrossSpectralDensity {
+    /// Frequency in hz
+    pub frequency: Vec<f64>,
+    /// Real part, the co-spectrum, in units of the series squared per hz
+    pub co: Vec<f64>,
+    /// Imaginary part, the quadrature spectrum, in units of the series squared per hz
+    pub quad: Vec<f64>,
+}
+
+/// Welch cross spectral density between two series sampled at the given rate in hz, using
+/// Hann windowed segments of the given length overlapping by half
+pub fn welch_cross_spectral_density(
+    x: &[f64],
+    y: &[f64],
+    sample_rate: f64,
+    segment_length: usize,
+) -> Result<CrossSpectralDensity, SpectralEstimationError> {
+    let (frequency, matrix) = welch_matrix(&[x, y], sample_rate, segment_length)?;
+    Ok(CrossSpectralDensity {
+        frequency,
+        co: matrix[0][1].iter().map(|c| c.0).collect(),
+        quad: matrix[0][1].iter().map(|c| c.1).collect(),
+    })
+}
+
+/// Heave energy spectrum and the first four directional fourier coefficients per frequency.
+/// The coefficients follow the NDBC convention, where a1 = r1 * cos(alpha1) and
+/// b1 = r1 * sin(alpha1) with alpha1 the direction waves are coming from in degrees clockwise
+/// from true north
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DirectionalCoefficients {
+    /// Frequency in hz
+    pub frequency: Vec<f64>,
+    /// Heave energy density in m2/hz
+    pub energy: Vec<f64>,
+    pub a1: Vec<f64>,
+    pub b1: Vec<f64>,
+    pub a2: Vec<f64>,
+    pub b2: Vec<f64>,
+}
+
+impl DirectionalCoefficients {
+    /// Estimates the coefficients from heave, north and west displacement series in meters
+    /// sampled at the given rate in hz, using Welch averaging over segments of the given
+    /// length. Heave, pitch and roll buoys can pass their north and west surface slopes in
+    /// place of the displacements, the coefficients are normalized so the scale cancels
+    pub fn from_displacements(
+        heave: &[f64],
+        north: &[f64],
+        west: &[f64],
+        sample_rate: f64,
+        segment_length: usize,
+    ) -> Result<Self, SpectralEstimationError> {
+        let (frequency, matrix) = welch_matrix(&[heave, north, west], sample_rate, segment_length)?;
+
+        let nf = frequency.len();
+        let mut energy = vec![0.0; nf];
+        let mut a1 = vec![0.0; nf];
+        let mut b1 = vec![0.0; nf];
+        let mut a2 = vec![0.0; nf];
+        let mut b2 = vec![0.0; nf];
+
+        for k in 0..nf {
+            let czz = matrix[0][0][k].0;
+            let cnn = matrix[1][1][k].0;
+            let cww = matrix[2][2][k].0;
+            let cnw = matrix[1][2][k].0;
+            // Horizontal displacement lags heave by 90 degrees in the direction of travel, so
+            // the quad spectrum of conj(Z) * N is positive for waves coming from the north
+            let qzn = matrix[0][1][k].1;
+            let qzw = matrix[0][2][k].1;
+
+            energy[k] = czz;
+
+            let horizontal = cnn + cww;
+            let norm = (czz * horizontal).sqrt();
+            if norm <= 0.0 || horizontal <= 0.0 {
+                continue;
+            }
+
+            a1[k] = qzn / norm;
+            b1[k] = -qzw / norm;
+            a2[k] = (cnn - cww) / horizontal;
+            b2[k] = -2.0 * cnw / horizontal;
+        }
+
+        Ok(DirectionalCoefficients {
+            frequency,
+            energy,
+            a1,
+            b1,
+            a2,
+            b2,
+        })
+    }
+
+    /// Builds the coefficients from values published in the NDBC frame, as CDIP does, where
+    /// a1 = r1 * cos(alpha1) and b1 = r1 * sin(alpha1) with alpha1 the compass direction the
+    /// waves come from. Missing values are kept and propagate through the conversions
+    pub fn new(
+        frequency: &[f64],
+        energy: &[f64],
+        a1: &[f64],
+        b1: &[f64],
+        a2: &[f64],
+        b2: &[f64],
+    ) -> Self {
+        DirectionalCoefficients {
+            frequency: frequency.to_vec(),
+            energy: energy.to_vec(),
+            a1: a1.to_vec(),
+            b1: b1.to_vec(),
+            a2: a2.to_vec(),
+            b2: b2.to_vec(),
+        }
+    }
+
+    /// Builds the coefficients from values published in the cartesian frame used by Spotter
+    /// buoys, where the waves come from 270 - atan2(b1, a1) degrees, rotating them to the
+    /// NDBC frame. Missing values are kept and propagate through the conversions
+    pub fn from_cartesian(
+        frequency: &[f64],
+        energy: &[f64],
+        a1: &[f64],
+        b1: &[f64],
+        a2: &[f64],
+        b2: &[f64],
+    ) -> Self {
+        let negate = |values: &[f64]| {
+            values
+                .iter()
+                .map(|v| if is_missing_value(*v) { *v } else { -v })
+                .collect::<Vec<f64>>()
+        };
+
+        DirectionalCoefficients {
+            frequency: frequency.to_vec(),
+            energy: energy.to_vec(),
+            a1: negate(b1),
+            b1: negate(a1),
+            a2: negate(a2),
+            b2: b2.to_vec(),
+        }
+    }
+
+    /// Builds the coefficients from the NDBC polar representation, with the mean and principal
+    /// wave directions in degrees (DirectionConvention::From)
+    pub fn from_polar(
+        frequency: &[f64],
+        energy: &[f64],
+        mean_wave_direction: &[f64],
+        primary_wave_direction: &[f64],
+        first_polar_coefficient: &[f64],
+        second_polar_coefficient: &[f64],
+    ) -> Self {
+        let nf = frequency.len();
+        let mut a1 = vec![0.0; nf];
+        let mut b1 = vec![0.0; nf];
+        let mut a2 = vec![0.0; nf];
+        let mut b2 = vec![0.0; nf];
+
+        for ik in 0..nf {
+            (a1[ik], b1[ik], a2[ik], b2[ik]) = polar_to_fourier(
+                mean_wave_direction[ik],
+                primary_wave_direction[ik],
+                first_polar_coefficient[ik],
+                second_polar_coefficient[ik],
+            );
+        }
+
+        DirectionalCoefficients {
+            frequency: frequency.to_vec(),
+            energy: energy.to_vec(),
+            a1,
+            b1,
+            a2,
+            b2,
+        }
+    }
+
+    /// The coefficients in polar form as (alpha1, alpha2, r1, r2) per frequency
+    fn polar(&self) -> Vec<(f64, f64, f64, f64)> {
+        (0..self.frequency.len())
+            .map(|ik| fourier_to_polar(self.a1[ik], self.b1[ik], self.a2[ik], self.b2[ik]))
+            .collect()
+    }
+
+    /// First polar coefficient r1 = sqrt(a1^2 + b1^2) per frequency
+    pub fn first_polar_coefficient(&self) -> Vec<f64> {
+        self.polar().iter().map(|p| p.2).collect()
+    }
+
+    /// Second polar coefficient r2 = sqrt(a2^2 + b2^2) per frequency
+    pub fn second_polar_coefficient(&self) -> Vec<f64> {
+        self.polar().iter().map(|p| p.3).collect()
+    }
+
+    /// Mean wave direction alpha1 in degrees per frequency, DirectionConvention::From
+    pub fn mean_wave_direction(&self) -> Vec<f64> {
+        self.polar().iter().map(|p| p.0).collect()
+    }
+
+    /// Principal wave direction alpha2 in degrees per frequency, DirectionConvention::From.
+    /// The 180 degree ambiguity of the second order coefficients is resolved towards the
+    /// mean wave direction
+    pub fn primary_wave_direction(&self) -> Vec<f64> {
+        self.polar().iter().map(|p| p.1).collect()
+    }
+}
+
+/// Whether a directional spectral value is missing. NDBC reports missing values as 999.0 or
+/// 999.9 while other sources leave them as NaN
+pub fn is_missing_directional_value(value: f64) -> bool {
+    is_missing_value(value)
+}
+
+/// Converts the first four directional fourier coefficients of a single frequency to the NDBC
+/// polar form (alpha1, alpha2, r1, r2), with directions in degrees (DirectionConvention::From).
+/// The 180 degree ambiguity of alpha2 is resolved towards alpha1. Any missing input gives NaN
+/// for every output
+pub fn fourier_to_polar(a1: f64, b1: f64, a2: f64, b2: f64) -> (f64, f64, f64, f64) {
+    if [a1, b1, a2, b2]
+        .iter()
+        .any(|v| is_missing_directional_value(*v))
+    {
+        return (f64::NAN, f64::NAN, f64::NAN, f64::NAN);
+    }
+
+    let alpha1 = b1.atan2(a1).to_degrees().rem_euclid(360.0);
+    let mut alpha2 = (0.5 * b2.atan2(a2).to_degrees()).rem_euclid(360.0);
+    let delta = (alpha2 - alpha1 + 180.0).rem_euclid(360.0) - 180.0;
+    if delta.abs() > 90.0 {
+        alpha2 = (alpha2 + 180.0).rem_euclid(360.0);
+    }
+
+    (alpha1, alpha2, a1.hypot(b1), a2.hypot(b2))
+}
+
+/// Converts the NDBC polar form of a single frequency, with directions in degrees
+/// (DirectionConvention::From), to the first four directional fourier coefficients
+/// (a1, b1, a2, b2). Any missing input gives NaN for every output
+pub fn polar_to_fourier(alpha1: f64, alpha2: f64, r1: f64, r2: f64) -> (f64, f64, f64, f64) {
+    if [alpha1, alpha2, r1, r2]
+        .iter()
+        .any(|v| is_missing_directional_value(*v))
+    {
+        return (f64::NAN, f64::NAN, f64::NAN, f64::NAN);
+    }
+
+    let alpha1 = alpha1.to_radians();
+    let alpha2 = alpha2.to_radians();
+    (
+        r1 * alpha1.cos(),
+        r1 * alpha1.sin(),
+        r2 * (2.0 * alpha2).cos(),
+        r2 * (2.0 * alpha2).sin(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::{
+        fourier_to_polar, is_missing_directional_value, polar_to_fourier,
+        welch_cross_spectral_density, welch_power_spectral_density, DirectionalCoefficients,
+    };
+
+    /// Deep water heave, north and west displacements of a long crested random sea with
+    /// significant height hs, coming from the given direction in degrees
+    fn displacements(
+        hs: f64,
+        from_direction: f64,
+        sample_rate: f64,
+        count: usize,
+    ) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+        let mut rng = StdRng::seed_from_u64(5);
+        let m0 = (hs / 4.0).powi(2);
+        let frequency = (0..60)
+            .map(|i| 0.07 + i as f64 * 0.001)
+            .collect::<Vec<f64>>();
+        let amplitude = (2.0 * m0 / frequency.len() as f64).sqrt();
+        let phases = frequency
+            .iter()
+            .map(|_| rng.gen_range(0.0..2.0 * PI))
+            .collect::<Vec<f64>>();
+
+        let toward = (from_direction + 180.0).to_radians();
+        let mut heave = vec![0.0; count];
+        let mut north = vec![0.0; count];
+        let mut west = vec![0.0; count];
+        for i in 0..count {
+            let t = i as f64 / sample_rate;
+            for (f, phase) in frequency.iter().zip(phases.iter()) {
+                let arg = 2.0 * PI * f * t + phase;
+                heave[i] += amplitude * arg.cos();
+                let horizontal = amplitude * arg.sin();
+                north[i] += horizontal * toward.cos();
+                west[i] -= horizontal * toward.sin();
+            }
+        }
+
+        (heave, north, west)
+    }
+
+    #[test]
+    fn test_welch_power_spectral_density() {
+        let sample_rate = 2.0;
+        let signal = (0..4096)
+            .map(|i| (2.0 * PI * 0.25 * i as f64 / sample_rate).cos())
+            .collect::<Vec<f64>>();
+
+        let (frequency, density) = welch_power_spectral_density(&signal, sample_rate, 256).unwrap();
+        assert_eq!(frequency.len(), 128);
+
+        // Parseval, the variance of a unit cosine is one half
+        let df = frequency[1] - frequency[0];
+        let variance = density.iter().sum::<f64>() * df;
+        assert!((variance - 0.5).abs() < 0.01);
+
+        let cross = welch_cross_spectral_density(&signal, &signal, sample_rate, 256).unwrap();
+        assert_eq!(cross.frequency, frequency);
+        for ((co, quad), d) in cross.co.iter().zip(cross.quad.iter()).zip(density.iter()) {
+            assert!((co - d).abs() < 1e-12);
+            assert!(quad.abs() < 1e-12);
+        }
+
+        assert!(welch_power_spectral_density(&signal[0..100], sample_rate, 256).is_err());
+        assert!(welch_cross_spectral_density(&signal, &signal[0..100], sample_rate, 256).is_err());
+    }
+
+    #[test]
+    fn test_coefficients_from_displacements() {
+        let sample_rate = 2.0;
+        for from_direction in [30.0, 135.0, 225.0, 300.0] {
+            let (heave, north, west) = displacements(2.0, from_direction, sample_rate, 4096);
+            let coefficients = DirectionalCoefficients::from_displacements(
+                &heave,
+                &north,
+                &west,
+                sample_rate,
+                256,
+            )
+            .unwrap();
+
+            let (ipeak, _) = coefficients
+                .energy
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+                .unwrap();
+
+            let df = coefficients.frequency[1] - coefficients.frequency[0];
+            let hm0 = 4.0 * (coefficients.energy.iter().sum::<f64>() * df).sqrt();
+            assert!((hm0 - 2.0).abs() < 0.1);
+
+            let alpha1 = coefficients.mean_wave_direction()[ipeak];
+            let alpha2 = coefficients.primary_wave_direction()[ipeak];
+            assert!((alpha1 - from_direction).abs() < 1.0);
+            assert!((alpha2 - from_direction).abs() < 1.0);
+
+            // A long crested sea is perfectly concentrated
+            assert!((coefficients.first_polar_coefficient()[ipeak] - 1.0).abs() < 0.01);
+            assert!((coefficients.second_polar_coefficient()[ipeak] - 1.0).abs() < 0.01);
+        }
+    }
+
+    #[test]
+    fn test_polar_fourier_round_trip() {
+        for (alpha1, alpha2, r1, r2) in [
+            (10.0, 15.0, 0.8, 0.6),
+            (200.0, 195.0, 0.5, 0.3),
+            (350.0, 5.0, 0.9, 0.7),
+        ] {
+            let (a1, b1, a2, b2) = polar_to_fourier(alpha1, alpha2, r1, r2);
+            let polar = fourier_to_polar(a1, b1, a2, b2);
+            assert!((polar.0 - alpha1).abs() < 1e-9);
+            assert!((polar.1 - alpha2).abs() < 1e-9);
+            assert!((polar.2 - r1).abs() < 1e-9);
+            assert!((polar.3 - r2).abs() < 1e-9);
+        }
+
+        // alpha2 is resolved towards alpha1
+        let (a1, b1, a2, b2) = polar_to_fourier(200.0, 20.0, 0.5, 0.3);
+        assert!((fourier_to_polar(a1, b1, a2, b2).1 - 200.0).abs() < 1e-9);
+
+        assert!(is_missing_directional_value(999.0));
+        assert!(is_missing_directional_value(f64::NAN));
+        assert!(!is_missing_directional_value(0.5));
+        assert!(polar_to_fourier(999.0, 10.0, 0.5, 0.5).0.is_nan());
+        assert!(fourier_to_polar(0.1, f64::NAN, 0.1, 0.1).2.is_nan());
+
+        // Spotter publishes coefficients in a cartesian frame, waves from 30 degrees travel
+        // towards 240 degrees counterclockwise from east
+        let theta = (270.0f64 - 30.0).to_radians();
+        let spotter = DirectionalCoefficients::from_cartesian(
+            &[0.1, 0.2],
+            &[1.0, 1.0],
+            &[0.8 * theta.cos(), 999.0],
+            &[0.8 * theta.sin(), 999.0],
+            &[0.6 * (2.0 * theta).cos(), 999.0],
+            &[0.6 * (2.0 * theta).sin(), 999.0],
+        );
+        assert!((spotter.mean_wave_direction()[0] - 30.0).abs() < 1e-9);
+        assert!((spotter.primary_wave_direction()[0] - 30.0).abs() < 1e-9);
+        assert!((spotter.first_polar_coefficient()[0] - 0.8).abs() < 1e-9);
+        assert!((spotter.second_polar_coefficient()[0] - 0.6).abs() < 1e-9);
+        assert!(spotter.mean_wave_direction()[1].is_nan());
+
+        let frequency = [0.1, 0.2];
+        let coefficients = DirectionalCoefficients::from_polar(
+            &frequency,
+            &[1.0, 999.9],
+            &[45.0, 999.0],
+            &[50.0, 999.0],
+            &[0.7, 999.0],
+            &[0.4, 999.0],
+        );
+        assert!((coefficients.mean_wave_direction()[0] - 45.0).abs() < 1e-9);
+        assert!((coefficients.primary_wave_direction()[0] - 50.0).abs() < 1e-9);
+        assert!(coefficients.first_polar_coefficient()[1].is_nan());
+    }
+}
@@ -0,0 +1,171 @@
+use std::f64::consts::PI;
+
+/// Euler-Mascheroni constant, used for the expected maximum of Rayleigh distributed heights
+const EULER_GAMMA: f64 = 0.5772;
+
+/// Ratio of H1/3 to sqrt(m0) for Rayleigh distributed wave heights
+const RAYLEIGH_H1_3: f64 = 4.004;
+
+/// Ratio of H1/10 to sqrt(m0) for Rayleigh distributed wave heights
+const RAYLEIGH_H1_10: f64 = 5.090;
+
+/// Frequency moment n of a one dimensional spectrum, with energy in m2/hz over the given
+/// frequency bins and bandwidths in hz
+pub fn spectral_moment(frequency: &[f64], energy: &[f64], df: &[f64], n: i32) -> f64 {
+    frequency
+        .iter()
+        .zip(energy.iter())
+        .zip(df.iter())
+        .map(|((f, e), d)| f.powi(n) * e * d)
+        .sum()
+}
+
+/// Goda (1970) peakedness parameter Qp = 2 / m0^2 * sum(f * S(f)^2 * df). Around 2 for a
+/// Pierson-Moskowitz spectrum, growing as the spectrum gets narrower
+pub fn goda_peakedness(frequency: &[f64], energy: &[f64], df: &[f64]) -> Option<f64> {
+    let m0 = spectral_moment(frequency, energy, df, 0);
+    if m0 <= 0.0 {
+        return None;
+    }
+
+    let sum = frequency
+        .iter()
+        .zip(energy.iter())
+        .zip(df.iter())
+        .map(|((f, e), d)| f * e.powi(2) * d)
+        .sum::<f64>();
+    Some(2.0 * sum / m0.powi(2))
+}
+
+/// Longuet-Higgins (1975) spectral bandwidth nu = sqrt(m0 * m2 / m1^2 - 1)
+pub fn spectral_bandwidth(frequency: &[f64], energy: &[f64], df: &[f64]) -> Option<f64> {
+    let m0 = spectral_moment(frequency, energy, df, 0);
+    let m1 = spectral_moment(frequency, energy, df, 1);
+    let m2 = spectral_moment(frequency, energy, df, 2);
+    if m0 <= 0.0 || m1 <= 0.0 {
+        return None;
+    }
+
+    Some((m0 * m2 / m1.powi(2) - 1.0).max(0.0).sqrt())
+}
+
+/// Cartwright and Longuet-Higgins (1956) spectral narrowness epsilon = sqrt(1 - m2^2 / (m0 * m4)),
+/// zero for a narrow spectrum and one for a broad spectrum
+pub fn spectral_narrowness(frequency: &[f64], energy: &[f64], df: &[f64]) -> Option<f64> {
+    let m0 = spectral_moment(frequency, energy, df, 0);
+    let m2 = spectral_moment(frequency, energy, df, 2);
+    let m4 = spectral_moment(frequency, energy, df, 4);
+    if m0 <= 0.0 || m4 <= 0.0 {
+        return None;
+    }
+
+    Some((1.0 - m2.powi(2) / (m0 * m4)).max(0.0).sqrt())
+}
+
+/// Expected number of consecutive waves higher than the significant wave height in a group,
+/// following the envelope statistics of Longuet-Higgins (1984), 1 / (sqrt(2 * pi) * nu * rho)
+/// with the threshold amplitude rho = sqrt(2) relative to sqrt(2 * m0)
+pub fn wave_group_length(frequency: &[f64], energy: &[f64], df: &[f64]) -> Option<f64> {
+    let nu = spectral_bandwidth(frequency, energy, df)?;
+    if nu <= 0.0 {
+        return None;
+    }
+
+    let rho = 2.0f64.sqrt();
+    Some(1.0 / ((2.0 * PI).sqrt() * nu * rho))
+}
+
+/// Mean height of the highest third of the waves for Rayleigh distributed heights, in meters
+/// given m0 in m2
+pub fn rayleigh_h1_3(m0: f64) -> f64 {
+    RAYLEIGH_H1_3 * m0.max(0.0).sqrt()
+}
+
+/// Mean height of the highest tenth of the waves for Rayleigh distributed heights, in meters
+/// given m0 in m2
+pub fn rayleigh_h1_10(m0: f64) -> f64 {
+    RAYLEIGH_H1_10 * m0.max(0.0).sqrt()
+}
+
+/// Expected maximum wave height in meters during a storm lasting the given duration in
+/// seconds, for Rayleigh distributed heights with the given m0 in m2 and mean zero crossing
+/// period in seconds (Longuet-Higgins, 1952)
+pub fn expected_max_wave_height(m0: f64, mean_period: f64, duration: f64) -> Option<f64> {
+    if m0 <= 0.0 || mean_period <= 0.0 {
+        return None;
+    }
+
+    let wave_count = duration / mean_period;
+    if wave_count <= 1.0 {
+        return None;
+    }
+
+    let hrms = (8.0 * m0).sqrt();
+    let ln_n = wave_count.ln();
+    Some(hrms * (ln_n.sqrt() + EULER_GAMMA / (2.0 * ln_n.sqrt())))
+}
+
+/// Test fixture of a 2 m, 10 s parametric frequency spectrum over count evenly spaced bins
+/// from 0.03 hz, returned as frequency, energy and bandwidths
+#[cfg(test)]
+pub(crate) fn test_frequency_spectrum(
+    shape: &crate::tools::parametric::SpectralShape,
+    count: usize,
+) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let frequency = (0..count)
+        .map(|i| 0.03 + i as f64 * 0.0025)
+        .collect::<Vec<f64>>();
+    let df = crate::tools::vector::diff(&frequency);
+    let energy = crate::tools::parametric::frequency_spectrum(shape, 2.0, 10.0, &frequency);
+    (frequency, energy, df)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::parametric::SpectralShape;
+
+    #[test]
+    fn test_spectral_shape_statistics() {
+        let (frequency, pm, df) = test_frequency_spectrum(&SpectralShape::PiersonMoskowitz, 400);
+        let (_, jonswap, _) = test_frequency_spectrum(&SpectralShape::Jonswap { gamma: 7.0 }, 400);
+
+        // The zeroth moment recovers the 2 m significant wave height
+        let m0 = spectral_moment(&frequency, &pm, &df, 0);
+        assert!((4.0 * m0.sqrt() - 2.0).abs() < 0.05);
+
+        // Qp is close to 2 for Pierson-Moskowitz and higher for peaked spectra
+        let qp_pm = goda_peakedness(&frequency, &pm, &df).unwrap();
+        let qp_jonswap = goda_peakedness(&frequency, &jonswap, &df).unwrap();
+        assert!((qp_pm - 2.0).abs() < 0.1);
+        assert!(qp_jonswap > qp_pm);
+
+        let nu_pm = spectral_bandwidth(&frequency, &pm, &df).unwrap();
+        let nu_jonswap = spectral_bandwidth(&frequency, &jonswap, &df).unwrap();
+        assert!(nu_jonswap < nu_pm);
+
+        let eps = spectral_narrowness(&frequency, &pm, &df).unwrap();
+        assert!(eps > 0.0 && eps < 1.0);
+
+        // Narrower spectra have longer groups
+        let group_pm = wave_group_length(&frequency, &pm, &df).unwrap();
+        let group_jonswap = wave_group_length(&frequency, &jonswap, &df).unwrap();
+        assert!(group_jonswap > group_pm);
+
+        assert!(goda_peakedness(&frequency, &vec![0.0; frequency.len()], &df).is_none());
+    }
+
+    #[test]
+    fn test_rayleigh_heights() {
+        let m0: f64 = 0.25;
+        assert!((rayleigh_h1_3(m0) - 2.002).abs() < 1e-9);
+        assert!((rayleigh_h1_10(m0) / rayleigh_h1_3(m0) - 1.271).abs() < 0.001);
+
+        // A three hour storm of 8 s waves gives Hmax close to 1.8 Hs
+        let hmax = expected_max_wave_height(m0, 8.0, 3.0 * 3600.0).unwrap();
+        let ratio = hmax / rayleigh_h1_3(m0);
+        assert!(ratio > 1.8 && ratio < 2.0);
+
+        assert!(expected_max_wave_height(m0, 8.0, 4.0).is_none());
+    }
+}
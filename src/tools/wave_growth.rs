@@ -0,0 +1,266 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    swell::Swell,
+    units::{Direction, UnitSystem},
+};
+
+use super::waves::{wave_energy, Error};
+
+const GRAVITY: f64 = 9.81;
+
+/// Ratio of the peak period to the significant period predicted by the SMB curves
+const SMB_PEAK_PERIOD_RATIO: f64 = 1.05;
+
+/// Empirical growth curves for wind waves generated over a limited fetch and duration
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub enum WaveGrowthModel {
+    /// Sverdrup, Munk and Bretschneider deep water curves as given in the Shore Protection
+    /// Manual (1977), using the 10 m wind speed. The curves predict the significant period,
+    /// which is converted to the peak period with Tp = 1.05 Ts
+    #[default]
+    Smb,
+    /// JONSWAP fetch limited growth (Hasselmann et al., 1973), using the 10 m wind speed.
+    /// The duration limit comes from the Shore Protection Manual (1984) and is evaluated with
+    /// the adjusted wind speed UA = 0.71 U10^1.23 it is stated for
+    Jonswap,
+    /// Coastal Engineering Manual (2002) curves scaled with the friction velocity
+    Cem,
+}
+
+/// Which constraint limits the growth of the waves
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GrowthLimit {
+    Fetch,
+    Duration,
+    FullyDeveloped,
+}
+
+/// Predicted wind wave conditions
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WaveGrowth {
+    /// Significant wave height in meters
+    pub wave_height: f64,
+    /// Peak period in seconds
+    pub period: f64,
+    pub limit: GrowthLimit,
+}
+
+impl WaveGrowthModel {
+    /// Predicts the wind waves for the given wind speed in m/s blowing over the given fetch in
+    /// meters for the given duration in seconds. The waves are limited by whichever of the
+    /// fetch or duration is shorter and never exceed the fully developed sea
+    pub fn predict(&self, wind_speed: f64, fetch: f64, duration: f64) -> Result<WaveGrowth, Error> {
+        if wind_speed <= 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "wind speed must be positive, got {wind_speed}"
+            )));
+        }
+        if fetch <= 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "fetch must be positive, got {fetch}"
+            )));
+        }
+        if duration <= 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "duration must be positive, got {duration}"
+            )));
+        }
+
+        // Every model is expressed in terms of a scaling velocity
+        let velocity = match self {
+            WaveGrowthModel::Smb | WaveGrowthModel::Jonswap => wind_speed,
+            WaveGrowthModel::Cem => {
+                let drag = 0.001 * (1.1 + 0.035 * wind_speed);
+                wind_speed * drag.sqrt()
+            }
+        };
+
+        let dimensionless_fetch = GRAVITY * fetch / velocity.powi(2);
+        let dimensionless_duration = GRAVITY * duration / velocity;
+
+        // The duration limited sea matches the fetch limited sea at an equivalent fetch
+        let equivalent_fetch = self.equivalent_fetch(dimensionless_duration, wind_speed);
+        let (effective_fetch, mut limit) = if equivalent_fetch < dimensionless_fetch {
+            (equivalent_fetch, GrowthLimit::Duration)
+        } else {
+            (dimensionless_fetch, GrowthLimit::Fetch)
+        };
+
+        let (mut height, period) = self.fetch_limited(effective_fetch);
+        let (max_height, max_period) = self.fully_developed();
+        if height >= max_height {
+            height = max_height;
+            limit = GrowthLimit::FullyDeveloped;
+        }
+        let period = period.min(max_period);
+
+        Ok(WaveGrowth {
+            wave_height: height * velocity.powi(2) / GRAVITY,
+            period: period * velocity / GRAVITY,
+            limit,
+        })
+    }
+
+    /// Predicts the wind waves as a swell component travelling with the wind, given the wind
+    /// speed in m/s, the direction the wind is coming from, the fetch in meters and the duration
+    /// in seconds
+    pub fn swell(
+        &self,
+        wind_speed: f64,
+        wind_direction: Direction,
+        fetch: f64,
+        duration: f64,
+    ) -> Result<Swell, Error> {
+        let growth = self.predict(wind_speed, fetch, duration)?;
+        Ok(Swell::new(
+            &UnitSystem::Metric,
+            growth.wave_height,
+            growth.period,
+            wind_direction,
+            None,
+            Some(wave_energy(growth.wave_height, growth.period)),
+            None,
+        ))
+    }
+
+    /// Dimensionless fetch limited wave height and peak period for the given dimensionless fetch
+    fn fetch_limited(&self, fetch: f64) -> (f64, f64) {
+        match self {
+            WaveGrowthModel::Smb => {
+                let height = 0.283 * (0.0125 * fetch.powf(0.42)).tanh();
+                let significant_period =
+                    2.0 * std::f64::consts::PI * 1.2 * (0.077 * fetch.powf(0.25)).tanh();
+                (height, SMB_PEAK_PERIOD_RATIO * significant_period)
+            }
+            WaveGrowthModel::Jonswap => (0.0016 * fetch.sqrt(), 0.286 * fetch.cbrt()),
+            WaveGrowthModel::Cem => (4.13e-2 * fetch.sqrt(), 0.651 * fetch.cbrt()),
+        }
+    }
+
+    /// Dimensionless wave height and peak period of the fully developed sea. The SMB curves only
+    /// approach their limit asymptotically so they share the Pierson-Moskowitz limit with JONSWAP,
+    /// gHs / U19.5^2 = 0.21 and gTp / U19.5 = 7.16 scaled to the 10 m wind speed with
+    /// U19.5 = 1.075 U10
+    fn fully_developed(&self) -> (f64, f64) {
+        match self {
+            WaveGrowthModel::Smb | WaveGrowthModel::Jonswap => (0.243, 7.70),
+            WaveGrowthModel::Cem => (211.5, 239.8),
+        }
+    }
+
+    /// Dimensionless fetch that produces the same sea as the given dimensionless duration, for
+    /// the given 10 m wind speed in m/s
+    fn equivalent_fetch(&self, duration: f64, wind_speed: f64) -> f64 {
+        match self {
+            WaveGrowthModel::Smb => {
+                // The SPM minimum duration curve has no closed form inverse, bisect on the log
+                // of the fetch where it is monotonic
+                let minimum_duration = |x: f64| {
+                    6.5882 * ((0.0161 * x.powi(2) - 0.3692 * x + 2.2024).sqrt() + 0.8798 * x).exp()
+                };
+
+                let (mut low, mut high) = (-10.0, 30.0);
+                for _ in 0..100 {
+                    let mid = 0.5 * (low + high);
+                    if minimum_duration(mid) < duration {
+                        low = mid;
+                    } else {
+                        high = mid;
+                    }
+                }
+                (0.5 * (low + high)).exp()
+            }
+            WaveGrowthModel::Jonswap => {
+                // The duration limit is stated for UA, rescale the duration to UA and the
+                // resulting fetch back to U10
+                let adjustment = 0.71 * wind_speed.powf(0.23);
+                (duration / adjustment / 68.8).powf(1.5) * adjustment.powi(2)
+            }
+            WaveGrowthModel::Cem => 5.23e-3 * duration.powf(1.5),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GrowthLimit, WaveGrowthModel};
+    use crate::units::Direction;
+
+    #[test]
+    fn test_fetch_limited_growth() {
+        // 20 m/s over 100 km for a full day is fetch limited
+        let smb = WaveGrowthModel::Smb
+            .predict(20.0, 100.0e3, 86400.0)
+            .unwrap();
+        assert_eq!(smb.limit, GrowthLimit::Fetch);
+        assert!((smb.wave_height - 3.69).abs() < 0.05);
+        assert!((smb.period - 7.98).abs() < 0.1);
+
+        let jonswap = WaveGrowthModel::Jonswap
+            .predict(20.0, 100.0e3, 86400.0)
+            .unwrap();
+        assert!((jonswap.wave_height - 3.23).abs() < 0.05);
+        assert!((jonswap.period - 7.87).abs() < 0.05);
+
+        let cem = WaveGrowthModel::Cem
+            .predict(20.0, 100.0e3, 86400.0)
+            .unwrap();
+        assert!((cem.wave_height - 3.54).abs() < 0.05);
+        assert!((cem.period - 6.24).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_duration_and_fully_developed_growth() {
+        for model in [
+            WaveGrowthModel::Smb,
+            WaveGrowthModel::Jonswap,
+            WaveGrowthModel::Cem,
+        ] {
+            // A short blow over a long fetch is duration limited
+            let fetch_limited = model.predict(15.0, 200.0e3, 86400.0).unwrap();
+            let duration_limited = model.predict(15.0, 200.0e3, 2.0 * 3600.0).unwrap();
+            assert_eq!(duration_limited.limit, GrowthLimit::Duration);
+            assert!(duration_limited.wave_height < fetch_limited.wave_height);
+            assert!(duration_limited.period < fetch_limited.period);
+
+            // Waves stop growing once the sea is fully developed
+            let developed = model.predict(10.0, 5000.0e3, 10.0 * 86400.0).unwrap();
+            let longer = model.predict(10.0, 10000.0e3, 20.0 * 86400.0).unwrap();
+            assert_eq!(developed.limit, GrowthLimit::FullyDeveloped);
+            assert_eq!(developed.wave_height, longer.wave_height);
+            assert!(developed.wave_height > 1.5 && developed.wave_height < 3.5);
+        }
+
+        // Two hours of a 15 m/s wind, UA = 19.85 m/s, give the same JONSWAP sea as the
+        // 14.94 km equivalent fetch of the Shore Protection Manual duration limit
+        let duration_limited = WaveGrowthModel::Jonswap
+            .predict(15.0, 200.0e3, 2.0 * 3600.0)
+            .unwrap();
+        let fetch_limited = WaveGrowthModel::Jonswap
+            .predict(15.0, 14.94e3, 86400.0)
+            .unwrap();
+        assert!((duration_limited.wave_height - 0.937).abs() < 0.001);
+        assert!((duration_limited.wave_height - fetch_limited.wave_height).abs() < 0.001);
+        assert!((duration_limited.period - fetch_limited.period).abs() < 0.001);
+
+        // The fully developed limit is the Pierson-Moskowitz sea for the 10 m wind
+        let developed = WaveGrowthModel::Jonswap
+            .predict(10.0, 5000.0e3, 10.0 * 86400.0)
+            .unwrap();
+        assert!((developed.wave_height - 2.48).abs() < 0.01);
+        assert!((developed.period - 7.85).abs() < 0.01);
+
+        assert!(WaveGrowthModel::Smb.predict(0.0, 1000.0, 3600.0).is_err());
+    }
+
+    #[test]
+    fn test_wave_growth_swell() {
+        let swell = WaveGrowthModel::Jonswap
+            .swell(15.0, Direction::from_degrees(225), 30.0e3, 6.0 * 3600.0)
+            .unwrap();
+        assert_eq!(swell.direction.get_value().degrees, 225);
+        assert!(swell.wave_height.get_value() > 0.5);
+        assert!(swell.energy.is_some());
+    }
+}
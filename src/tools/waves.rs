@@ -1,59 +1,57 @@
-use std::{collections::HashSet, f64::consts::PI, ops::Sub, vec};
+use std::{f64::consts::PI, fmt, ops::Sub, vec};
 
 use chrono::{DateTime, Utc};
-use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     dimensional_data::DimensionalData,
-    swell::Swell,
+    swell::{Swell, SwellTrack},
+    tools::{
+        analysis::hungarian,
+        dispersion::{group_ratio, wavenumber},
+    },
     units::{direction::DirectionConvention, Direction, Unit, UnitSystem},
 };
 
 const GRAVITY: f64 = 9.81;
 
+/// Wind speed multiplier used by the wave age criterion to separate wind sea from swell,
+/// (Hanson & Phillips, 2001). Same value as WSMULT in WW3
+pub const WSMULT: f64 = 1.7;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Error {
+    /// An iterative solution did not converge
     ConvergenceFailure,
-    OutOfRange,
+    /// Waves with an incident angle in degrees at or beyond 90 degrees travel away from shore
+    IncidentAngleOutOfRange(f64),
+    /// A parameter is outside of its physical range
+    InvalidParameter(String),
+    /// A required value is missing from the input data
+    MissingData(String),
 }
 
-/// Computes the wavelength for a wave with the given period and depth. Units are metric, gravity is 9.81 m/s.
-pub fn ldis(period: f64, depth: f64) -> Result<f64, Error> {
-    const EPS: f64 = 0.000001;
-    const MAX_ITERATION: usize = 50;
-
-    let omega = 2.0 * PI / period;
-    let d = omega.powi(2) * depth * GRAVITY;
-
-    let mut iter: usize = 0;
-    let mut err: f64 = 1.0;
-
-    let mut xf: f64 = 0.0;
-    let mut xo: f64;
-    let mut f: f64;
-    let mut df: f64;
-
-    // Make an initial guess for non dimensional solutions
-    if d >= 1.0 {
-        xo = d;
-    } else {
-        xo = d.sqrt();
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ConvergenceFailure => write!(f, "iterative solution failed to converge"),
+            Error::IncidentAngleOutOfRange(angle) => write!(
+                f,
+                "incident angle of {angle:.1} degrees is out of range, waves must approach within 90 degrees of the shore normal"
+            ),
+            Error::InvalidParameter(s) => write!(f, "invalid parameter: {s}"),
+            Error::MissingData(s) => write!(f, "missing data: {s}"),
+        }
     }
+}
 
-    // Solve using newton raphson iteration
-    while (err > EPS) && (iter < MAX_ITERATION) {
-        f = xo - (d / xo.tanh());
-        df = 1.0 + (d / xo.sinh().powi(2));
-        xf = xo - (f / df);
-        err = ((xf - xo) / xo).abs();
-        xo = xf;
-        iter += 1;
-    }
+impl std::error::Error for Error {}
 
-    if iter >= MAX_ITERATION {
-        Err(Error::ConvergenceFailure)
-    } else {
-        Ok(2.0 * PI * depth / xf)
-    }
+/// Computes the wavelength for a wave with the given period and depth. Units are metric, gravity is 9.81 m/s.
+pub fn ldis(period: f64, depth: f64) -> Result<f64, Error> {
+    wavenumber(period, depth)
+        .map(|k| 2.0 * PI / k)
+        .ok_or(Error::ConvergenceFailure)
 }
 
 /// Calculate wavenumber and group velocity from the improved
@@ -105,6 +103,19 @@ pub fn celerity(freq: f64, depth: Option<f64>) -> f64 {
     }
 }
 
+/// Wave group velocity Cg from the linear dispersion relation, the same solution used by
+/// `LinearWave`, falling back to the Chen and Thomson approximation if it does not converge.
+/// When depth is not supplied use deep water approximation
+pub fn group_velocity(freq: f64, depth: Option<f64>) -> f64 {
+    if let Some(depth) = depth {
+        let angle_freq = 2.0 * PI * freq;
+        let k = wavenumber(1.0 / freq, depth).unwrap_or_else(|| wavenuma(angle_freq, depth));
+        group_ratio(k * depth) * angle_freq / k
+    } else {
+        0.5 * celerity(freq, None)
+    }
+}
+
 /// Wavelength L
 /// When depth is not suppliked use deep water approximation
 pub fn wavelength(freq: f64, depth: Option<f64>) -> f64 {
@@ -121,6 +132,260 @@ pub fn wave_energy(hs: f64, tp: f64) -> f64 {
     (1029.0 * ((9.81f64).powf(2.0)) / (16.0 * PI)) * hs.powf(2.0) * tp.powf(2.0) / 1000.0
 }
 
+/// Ratio of the energy period to the peak period used when only the peak period is known,
+/// the value for a JONSWAP spectrum with gamma 3.3 (IEC TS 62600-101)
+pub const ENERGY_PERIOD_RATIO: f64 = 0.9;
+
+/// Computes the wave power, or energy flux per unit crest length, in kW/m for the given
+/// significant wave height in meters and energy period in seconds. When depth is not supplied
+/// use deep water approximation
+pub fn wave_power(hs: f64, energy_period: f64, depth: Option<f64>) -> f64 {
+    if energy_period <= 0.0 {
+        return 0.0;
+    }
+
+    let energy = UnitSystem::Metric.density_of_seawater() * GRAVITY * hs.powi(2) / 16.0;
+    energy * group_velocity(1.0 / energy_period, depth) / 1000.0
+}
+
+/// Breaker index gamma = Hb / hb used to relate breaking height and depth when a model does
+/// not predict the breaking depth itself (McCowan, 1894)
+pub const MCCOWAN_BREAKER_INDEX: f64 = 0.78;
+
+/// Breaker type classified from the deep water Iribarren number (Battjes, 1974)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BreakerType {
+    Spilling,
+    Plunging,
+    Surging,
+}
+
+impl BreakerType {
+    pub fn from_iribarren(iribarren: f64) -> BreakerType {
+        if iribarren < 0.5 {
+            BreakerType::Spilling
+        } else if iribarren < 3.3 {
+            BreakerType::Plunging
+        } else {
+            BreakerType::Surging
+        }
+    }
+}
+
+/// Deep water Iribarren number (surf similarity parameter) tan(beta) / sqrt(H0 / L0) for the
+/// given beach slope, deep water wave height in meters and period in seconds
+pub fn iribarren_number(beach_slope: f64, deep_water_wave_height: f64, period: f64) -> f64 {
+    let deep_wavelength = (GRAVITY * period.powi(2)) / (2.0 * PI);
+    beach_slope / (deep_water_wave_height / deep_wavelength).sqrt()
+}
+
+/// A wave at its breaking point
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BreakingWave {
+    /// Breaking wave height in meters
+    pub height: f64,
+    /// Breaking water depth in meters
+    pub depth: f64,
+    pub breaker_type: BreakerType,
+    /// Deep water Iribarren number the breaker type is classified from
+    pub iribarren: f64,
+}
+
+/// Empirical formulations for the height and depth of breaking waves
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub enum BreakingModel {
+    /// Komar & Gaudiano (1975) breaking height with the Weggel (1972) breaking depth
+    #[default]
+    KomarGaudiano,
+    /// Larson, Hoan & Hanson (2010) direct solution of energy flux conservation and Snell's
+    /// law at incipient breaking. Solved iteratively here rather than with their polynomial fit
+    Larson,
+    /// Linear shoaling and refraction until the wave height reaches gamma times the depth
+    BreakerIndex { gamma: f64 },
+}
+
+impl BreakingModel {
+    /// Solves for the breaking wave given the deep water wave height in meters, period in
+    /// seconds, deep water incident angle in degrees relative to the shore normal and the beach
+    /// slope. The water depth in meters is where the Komar & Gaudiano model evaluates
+    /// refraction, the other models refract all the way from deep water
+    pub fn break_wave(
+        &self,
+        period: f64,
+        incident_angle: f64,
+        deep_water_wave_height: f64,
+        beach_slope: f64,
+        water_depth: f64,
+    ) -> Result<BreakingWave, Error> {
+        if period <= 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "period must be positive, got {period}"
+            )));
+        }
+        if deep_water_wave_height <= 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "wave height must be positive, got {deep_water_wave_height}"
+            )));
+        }
+        if beach_slope <= 0.0 {
+            return Err(Error::InvalidParameter(format!(
+                "beach slope must be positive, got {beach_slope}"
+            )));
+        }
+        if incident_angle.abs() >= 90.0 {
+            return Err(Error::IncidentAngleOutOfRange(incident_angle));
+        }
+
+        let (height, depth) = match self {
+            BreakingModel::KomarGaudiano => break_wave(
+                period,
+                incident_angle,
+                deep_water_wave_height,
+                beach_slope,
+                water_depth,
+            )?,
+            BreakingModel::Larson => {
+                larson_break_wave(period, incident_angle, deep_water_wave_height)?
+            }
+            BreakingModel::BreakerIndex { gamma } => {
+                breaker_index_break_wave(period, incident_angle, deep_water_wave_height, *gamma)?
+            }
+        };
+
+        let iribarren = iribarren_number(beach_slope, deep_water_wave_height, period);
+
+        Ok(BreakingWave {
+            height,
+            depth,
+            breaker_type: BreakerType::from_iribarren(iribarren),
+            iribarren,
+        })
+    }
+}
+
+/// Larson et al. (2010) breaking height and depth, using the McCowan breaker index
+fn larson_break_wave(
+    period: f64,
+    incident_angle: f64,
+    deep_water_wave_height: f64,
+) -> Result<(f64, f64), Error> {
+    const EPS: f64 = 0.000001;
+    const MAX_ITERATION: usize = 50;
+
+    let gamma = MCCOWAN_BREAKER_INDEX;
+    let incident_angle = incident_angle.to_radians();
+    let deep_celerity = (GRAVITY * period) / (2.0 * PI);
+
+    // Breaking height ignoring refraction between the breakpoint and shore normal incidence
+    let base_height = (deep_water_wave_height.powi(2) * 0.5 * deep_celerity * incident_angle.cos()
+        / (GRAVITY / gamma).sqrt())
+    .powf(0.4);
+
+    // Correction lambda solves lambda^5 * (1 - xi * lambda) = 1, where the second term is the
+    // cosine squared of the breaking angle from Snell's law
+    let xi = (incident_angle.sin() / deep_celerity).powi(2) * GRAVITY * base_height / gamma;
+    let mut lambda: f64 = 1.0;
+    let mut iter = 0;
+    loop {
+        let f = lambda.powi(5) * (1.0 - xi * lambda) - 1.0;
+        let df = 5.0 * lambda.powi(4) - 6.0 * xi * lambda.powi(5);
+        if df.abs() <= f64::EPSILON {
+            return Err(Error::ConvergenceFailure);
+        }
+
+        let next = lambda - f / df;
+        iter += 1;
+        if ((next - lambda) / lambda).abs() < EPS {
+            lambda = next;
+            break;
+        }
+        if iter >= MAX_ITERATION {
+            return Err(Error::ConvergenceFailure);
+        }
+        lambda = next;
+    }
+
+    let breaking_wave_height = lambda * base_height;
+    Ok((breaking_wave_height, breaking_wave_height / gamma))
+}
+
+/// Shoals and refracts the wave over straight and parallel contours until its height reaches
+/// gamma times the water depth
+fn breaker_index_break_wave(
+    period: f64,
+    incident_angle: f64,
+    deep_water_wave_height: f64,
+    gamma: f64,
+) -> Result<(f64, f64), Error> {
+    if gamma <= 0.0 {
+        return Err(Error::InvalidParameter(format!(
+            "breaker index must be positive, got {gamma}"
+        )));
+    }
+
+    let excess = |depth: f64| -> Result<f64, Error> {
+        let wavelength = ldis(period, depth)?;
+        let ks = shoaling_coefficient(wavelength, depth);
+        let (kr, _) = refraction_coefficient(wavelength, depth, incident_angle);
+        Ok(deep_water_wave_height * ks * kr - gamma * depth)
+    };
+
+    let mut deep = (GRAVITY * period.powi(2)) / (2.0 * PI);
+    let mut shallow = 0.001;
+    if excess(deep)? >= 0.0 {
+        return Err(Error::InvalidParameter(format!(
+            "wave height of {deep_water_wave_height} m is already breaking in deep water"
+        )));
+    }
+
+    // Bisect for the depth where the shoaled height meets the breaker index
+    for _ in 0..60 {
+        let mid = 0.5 * (deep + shallow);
+        if excess(mid)? >= 0.0 {
+            shallow = mid;
+        } else {
+            deep = mid;
+        }
+    }
+
+    let breaking_water_depth = 0.5 * (deep + shallow);
+    Ok((gamma * breaking_water_depth, breaking_water_depth))
+}
+
+/// Computes an estimate of the breaking wave for a given swell and beach conditions using the
+/// given breaking model. The beach angle is the shore normal direction in degrees
+pub fn estimate_breaking_wave(
+    swell: &Swell,
+    beach_angle: f64,
+    beach_slope: f64,
+    water_depth: f64,
+    model: &BreakingModel,
+) -> Result<BreakingWave, Error> {
+    let deep_water_wave_height = swell
+        .wave_height
+        .value
+        .ok_or_else(|| Error::MissingData("swell wave height".to_string()))?;
+    let period = swell
+        .period
+        .value
+        .ok_or_else(|| Error::MissingData("swell period".to_string()))?;
+    let direction = swell
+        .direction
+        .value
+        .as_ref()
+        .ok_or_else(|| Error::MissingData("swell direction".to_string()))?;
+
+    let incident_angle = (direction.degrees as f64 - beach_angle + 540.0).rem_euclid(360.0) - 180.0;
+
+    model.break_wave(
+        period,
+        incident_angle,
+        deep_water_wave_height,
+        beach_slope,
+        water_depth,
+    )
+}
+
 /// Computes an estimate of the wave height for a given swell and beach conditions.
 pub fn estimate_breaking_wave_height(
     swell: &Swell,
@@ -128,24 +393,14 @@ pub fn estimate_breaking_wave_height(
     beach_slope: f64,
     water_depth: f64,
 ) -> Result<f64, Error> {
-    let deep_water_wave_height = swell.wave_height.value.as_ref().unwrap();
-    let period = swell.period.value.as_ref().unwrap();
-    let incident_angle =
-        (swell.direction.value.as_ref().unwrap().degrees as f64 - beach_angle).abs() as i32 % 360;
-
-    if incident_angle > 90 {
-        return Err(Error::OutOfRange);
-    }
-
-    let (breaking_wave_height, _) = break_wave(
-        *period,
-        incident_angle as f64,
-        *deep_water_wave_height,
+    estimate_breaking_wave(
+        swell,
+        beach_angle,
         beach_slope,
         water_depth,
-    )?;
-
-    Ok(breaking_wave_height)
+        &BreakingModel::KomarGaudiano,
+    )
+    .map(|b| b.height)
 }
 
 /// Solves for the Breaking Wave Height and Breaking Water Depth given a swell and beach conditions. All units are metric, degrees, and gravity is 9.81 m/s.
@@ -225,6 +480,49 @@ pub fn steepness_coefficient(zero_moment: f64, second_moment: f64) -> f64 {
     (8.0 * PI * second_moment) / (9.81 * zero_moment.sqrt())
 }
 
+/// Whether a wave component with the given phase speed is forced by the wind under the wave age
+/// criterion (Hanson & Phillips, 2001). The wind angle is the angle between the wave and wind
+/// directions in degrees. Units are metric
+pub fn is_wind_sea(celerity: f64, wind_speed: f64, wind_angle: f64) -> bool {
+    let upar = WSMULT * wind_speed * wind_angle.to_radians().cos().max(0.0);
+    upar > celerity
+}
+
+/// Separation frequency in hz between wind sea and swell for waves travelling with the wind
+/// under the wave age criterion, (Hanson & Phillips, 2001). This is the frequency where the
+/// phase speed matches WSMULT times the wind speed, all components aligned with the wind above it
+/// are wind sea. When depth is not supplied use deep water approximation. Returns None when
+/// there is no wind. In shallow water the wind outruns every wave once WSMULT times the wind
+/// speed exceeds sqrt(g h), so all components aligned with the wind are wind sea and the lowest
+/// searched frequency of 0.001 hz is returned. Units are metric
+pub fn wind_sea_separation_frequency(wind_speed: f64, depth: Option<f64>) -> Option<f64> {
+    const MAX_ITERATION: usize = 100;
+    const LOWEST_FREQUENCY: f64 = 0.001;
+
+    if wind_speed <= 0.0 {
+        return None;
+    }
+
+    let target = WSMULT * wind_speed;
+
+    // Phase speed decreases with frequency, so bisect between a very long and very short wave
+    let mut low = LOWEST_FREQUENCY;
+    let mut high = 10.0;
+    if celerity(low, depth) <= target {
+        return Some(LOWEST_FREQUENCY);
+    }
+    for _ in 0..MAX_ITERATION {
+        let mid = 0.5 * (low + high);
+        if celerity(mid, depth) > target {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    Some(0.5 * (low + high))
+}
+
 /// Rate of change of the wind-sea peak wave frequency.
 /// Based on fetch-limited relationships, (Ewans & Kibblewhite, 1986).
 ///
@@ -244,10 +542,22 @@ pub fn dfp_swell_sea(dt: f64, distance: f64) -> f64 {
     dt * 9.81 / (4.0 * PI * distance)
 }
 
+/// Calculates the distance in meters to the source of a swell whose peak frequency rises at the
+/// given rate in hz per second, the inverse of `dfp_swell_sea`
+///
+/// Units are metric, gravity is 9.81 m/s
+/// Based on the swell dispersion relationship derived by Snodgrass et al (1966).
+pub fn swell_source_distance(dfp_dt: f64) -> f64 {
+    9.81 / (4.0 * PI * dfp_dt)
+}
+
 /// Takes a timeseries of wind, times, and wave partitions and returns
 /// the wave partitions with unique partition ids. This means that the
 /// wave partitions are tracked through time, and every partition id is
-/// unique over the entire timeseries.
+/// unique over the entire timeseries. Partitions in consecutive time steps
+/// are matched with optimal assignment, minimizing the total score of the
+/// matched direction and period differences. Partitions in a time step that does not come
+/// after the previous one cannot be scored, so they start new tracks.
 /// Units are metric, gravity is 9.81 m/s
 ///
 /// Adapted from wavespectra library
@@ -271,11 +581,6 @@ pub fn track_partitions(
         return inputs.iter().map(|x| x.1.to_vec()).collect();
     }
 
-    // Since all of the partitions are candidates for tracking in the first time step,
-    // we can start the partition counter at the length of the consecutive_partitions
-    // asumming that the first time steps
-    let mut partition_count = inputs.first().as_ref().unwrap().1.len();
-
     // Create a new partition map. There is definitely a better way to do this, but lets just get the
     // logic working first without modifying the input data
     let mut partition_map: Vec<Vec<(usize, f64)>> = inputs
@@ -287,6 +592,15 @@ pub fn track_partitions(
         })
         .collect();
 
+    // Since all of the partitions are candidates for tracking in the first time step,
+    // new ids start after the largest id of the first time step so they never collide
+    let mut partition_count = partition_map[0]
+        .iter()
+        .map(|(id, _)| id + 1)
+        .max()
+        .unwrap_or(0)
+        .max(partition_map[0].len());
+
     for i in 1..partition_map.len() {
         let prev = inputs.get(i - 1).unwrap();
         let current = inputs.get(i).unwrap();
@@ -294,55 +608,52 @@ pub fn track_partitions(
         let dt = (current.0 - prev.0).num_seconds() as f64;
         let dfp_swell = dfp_swell_sea(dt, swell_source_distance);
 
-        let matches = current
+        // Score every current partition against every previous partition
+        let scores = current
             .1
             .iter()
-            .enumerate()
-            .map(|(icp, p)| {
+            .map(|p| {
                 let partition_dir = p.direction.get_value().degrees;
                 let partition_period = p.period.get_value();
 
                 prev.1
                     .iter()
-                    .enumerate()
-                    .map(|(ipp, prev_p)| {
+                    .map(|prev_p| {
                         let prev_partition_dir = prev_p.direction.get_value().degrees;
                         let dir_delta =
                             ((((partition_dir - prev_partition_dir) + 180) % 360) - 180).abs();
-                        //println!("Dir delta: {} partition dir: {}, prev partition dir: {}", dir_delta, partition_dir, prev_partition_dir);
                         let period_delta = (partition_period - prev_p.period.get_value()).abs();
 
-                        let score = if dir_delta > max_dir_delta as i32 {
+                        let score = if dt <= 0.0 || dir_delta > max_dir_delta as i32 {
                             999.99
                         } else {
                             (dir_delta as f64 / max_dir_delta).abs()
                                 + (period_delta / dfp_swell).abs()
                         }
                         .abs();
-                        (icp, ipp, score)
+
+                        if score.is_finite() {
+                            score
+                        } else {
+                            999.99
+                        }
                     })
-                    .min_by(|(_i, __i, a), (_ii, __ii, b)| a.partial_cmp(b).unwrap())
-            })
-            .sorted_by(|a, b| {
-                a.as_ref()
-                    .unwrap()
-                    .2
-                    .partial_cmp(&b.as_ref().unwrap().2)
-                    .unwrap()
+                    .collect::<Vec<f64>>()
             })
             .collect::<Vec<_>>();
 
-        let mut hit: HashSet<usize> = HashSet::new();
-        for m in matches {
-            if let Some((icp, ipp, score)) = m {
-                let (ipp, _) = partition_map[i - 1][ipp];
-                if hit.contains(&ipp) || score > 999.0 {
+        // Match partitions so the total score is minimized, any partition that is left without
+        // a valid match starts a new track
+        let assignment = hungarian(&scores);
+        for (icp, ipp) in assignment.iter().enumerate() {
+            match ipp {
+                Some(ipp) if scores[icp][*ipp] <= 999.0 => {
+                    let (id, _) = partition_map[i - 1][*ipp];
+                    partition_map[i][icp] = (id, scores[icp][*ipp]);
+                }
+                _ => {
                     partition_map[i][icp] = (partition_count, 0.0);
                     partition_count += 1;
-                } else {
-                    //println!("Hit: {} score {}", ipp, score);
-                    partition_map[i][icp] = (ipp, score);
-                    hit.insert(ipp);
                 }
             }
         }
@@ -367,6 +678,36 @@ pub fn track_partitions(
         .collect()
 }
 
+/// Tracks the wave partitions through time like `track_partitions`, grouping every tracked
+/// partition into a swell track ordered by start time
+pub fn track_swell_systems(
+    inputs: &[(DateTime<Utc>, Vec<Swell>)],
+    max_dir_delta: f64,
+    swell_source_distance: f64,
+) -> Vec<SwellTrack> {
+    let tracked = track_partitions(inputs, max_dir_delta, swell_source_distance);
+
+    let mut tracks: Vec<SwellTrack> = Vec::new();
+    for ((date, _), partitions) in inputs.iter().zip(tracked) {
+        for partition in partitions {
+            let Some(id) = partition.partition else {
+                continue;
+            };
+
+            match tracks.iter_mut().find(|t| t.id == id) {
+                Some(track) => track.series.push((*date, partition)),
+                None => tracks.push(SwellTrack {
+                    id,
+                    series: vec![(*date, partition)],
+                }),
+            }
+        }
+    }
+
+    tracks.sort_by_key(|t| (t.start(), t.id));
+    tracks
+}
+
 /// Calculates the Compute mean parameters per swell component given a discretized spectral signal
 /// Ported from WW3 code: PTMEAN in w3partmd.f90
 pub fn pt_mean(
@@ -384,7 +725,6 @@ pub fn pt_mean(
 ) -> (Swell, Vec<Swell>) {
     const TPI: f64 = 2.0 * PI;
     let dera = 1.0f64.atan() / 45.0;
-    const WSMULT: f64 = 1.7;
 
     let sig = (0..frequency.len() + 2)
         .map(|ik| {
@@ -672,3 +1012,148 @@ pub fn pt_mean(
 
     (summary, components)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        estimate_breaking_wave, estimate_breaking_wave_height, group_velocity, iribarren_number,
+        ldis, track_partitions, BreakerType, BreakingModel, Error,
+    };
+    use crate::{
+        swell::Swell,
+        units::{Direction, UnitSystem},
+    };
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_group_velocity() {
+        // Shallow water waves are non dispersive, cg = sqrt(g h)
+        let shallow = (9.81f64 * 2.0).sqrt();
+        assert!((group_velocity(0.01, Some(2.0)) - shallow).abs() / shallow < 0.01);
+
+        // Deep water waves travel at half of the phase speed
+        let deep = 0.5 * 9.81 / (2.0 * std::f64::consts::PI * 0.1);
+        assert!((group_velocity(0.1, Some(1000.0)) - deep).abs() / deep < 1e-3);
+        assert!((group_velocity(0.1, None) - deep).abs() / deep < 0.01);
+    }
+
+    #[test]
+    fn test_ldis() {
+        // Deep water wavelength is g T^2 / 2 pi
+        let deep = ldis(10.0, 500.0).ok().unwrap();
+        assert!((deep - 156.13).abs() < 0.01);
+
+        let intermediate = ldis(8.0, 10.0).ok().unwrap();
+        assert!((intermediate - 70.90).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_breaking_models() {
+        let models = [
+            BreakingModel::KomarGaudiano,
+            BreakingModel::Larson,
+            BreakingModel::BreakerIndex { gamma: 0.78 },
+        ];
+
+        for model in models.iter() {
+            let wave = model.break_wave(10.0, 10.0, 1.5, 0.02, 10.0).unwrap();
+
+            // A 1.5 m 10 s swell shoals before it breaks in a couple meters of water
+            assert!(wave.height > 1.5 && wave.height < 3.0);
+            assert!(wave.depth > 1.5 && wave.depth < 4.0);
+            assert_eq!(wave.breaker_type, BreakerType::Spilling);
+        }
+
+        // More oblique incident angles break lower
+        let straight = BreakingModel::Larson
+            .break_wave(10.0, 0.0, 1.5, 0.02, 10.0)
+            .unwrap();
+        let oblique = BreakingModel::Larson
+            .break_wave(10.0, 45.0, 1.5, 0.02, 10.0)
+            .unwrap();
+        assert!(oblique.height < straight.height);
+
+        let index = BreakingModel::BreakerIndex { gamma: 0.78 }
+            .break_wave(12.0, 0.0, 1.0, 0.1, 10.0)
+            .unwrap();
+        assert!((index.height / index.depth - 0.78).abs() < 1e-6);
+        assert_eq!(index.breaker_type, BreakerType::Plunging);
+
+        assert!(matches!(
+            BreakingModel::Larson.break_wave(10.0, 95.0, 1.5, 0.02, 10.0),
+            Err(Error::IncidentAngleOutOfRange(_))
+        ));
+        assert!(matches!(
+            BreakingModel::Larson.break_wave(-1.0, 0.0, 1.5, 0.02, 10.0),
+            Err(Error::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn test_breaker_type() {
+        assert_eq!(BreakerType::from_iribarren(0.3), BreakerType::Spilling);
+        assert_eq!(BreakerType::from_iribarren(1.0), BreakerType::Plunging);
+        assert_eq!(BreakerType::from_iribarren(4.0), BreakerType::Surging);
+
+        // A 1 m 10 s wave on a 1:10 slope
+        assert!((iribarren_number(0.1, 1.0, 10.0) - 1.249).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_estimate_breaking_wave() {
+        let swell = Swell::new(
+            &UnitSystem::Metric,
+            1.5,
+            10.0,
+            Direction::from_degrees(350),
+            None,
+            None,
+            None,
+        );
+
+        // The incident angle wraps through north
+        let height = estimate_breaking_wave_height(&swell, 10.0, 0.02, 10.0).unwrap();
+        assert!(height > 1.5);
+
+        let err =
+            estimate_breaking_wave(&swell, 180.0, 0.02, 10.0, &BreakingModel::Larson).unwrap_err();
+        assert!(matches!(err, Error::IncidentAngleOutOfRange(_)));
+        let err: Box<dyn std::error::Error> = Box::new(err);
+        assert!(err.to_string().contains("incident angle"));
+    }
+
+    #[test]
+    fn test_track_partitions_duplicate_timestamps() {
+        let swell = |period: f64, partition: usize| {
+            Swell::new(
+                &UnitSystem::Metric,
+                1.0,
+                period,
+                Direction::from_degrees(270),
+                None,
+                None,
+                Some(partition),
+            )
+        };
+
+        let t0 = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let t1 = Utc.with_ymd_and_hms(2024, 1, 1, 3, 0, 0).unwrap();
+        let inputs = vec![
+            (t0, vec![swell(12.0, 0), swell(8.0, 1)]),
+            (t0, vec![swell(12.0, 0), swell(8.0, 1)]),
+            (t1, vec![swell(12.0, 0), swell(8.0, 1)]),
+        ];
+
+        // A repeated time step must not stall the matching and starts new tracks
+        let tracked = track_partitions(&inputs, 30.0, 1e6);
+        assert_eq!(tracked.len(), 3);
+        assert_eq!(tracked[1][0].partition, Some(2));
+        assert_eq!(tracked[1][1].partition, Some(3));
+        assert_eq!(tracked[2][0].partition, Some(2));
+        assert_eq!(tracked[2][1].partition, Some(3));
+
+        // A zero direction tolerance cannot be scored either
+        let tracked = track_partitions(&inputs[1..], 0.0, 1e6);
+        assert_eq!(tracked.len(), 2);
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+/// Individual waves found by a zero up-crossing analysis of a surface elevation series
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ZeroCrossingAnalysis {
+    /// Crest to trough height of every wave in meters
+    pub heights: Vec<f64>,
+    /// Zero up-crossing period of every wave in seconds
+    pub periods: Vec<f64>,
+}
+
+impl ZeroCrossingAnalysis {
+    /// Number of complete waves in the series
+    pub fn wave_count(&self) -> usize {
+        self.heights.len()
+    }
+
+    /// Height of the largest wave in meters
+    pub fn hmax(&self) -> Option<f64> {
+        self.heights.iter().cloned().reduce(f64::max)
+    }
+
+    /// Mean height of the highest third of the waves in meters
+    pub fn h1_3(&self) -> Option<f64> {
+        self.highest_fraction(3).map(|(h, _)| h)
+    }
+
+    /// Mean period of the highest third of the waves in seconds
+    pub fn t1_3(&self) -> Option<f64> {
+        self.highest_fraction(3).map(|(_, t)| t)
+    }
+
+    /// Mean height of the highest tenth of the waves in meters
+    pub fn h1_10(&self) -> Option<f64> {
+        self.highest_fraction(10).map(|(h, _)| h)
+    }
+
+    /// Mean zero up-crossing period in seconds
+    pub fn mean_period(&self) -> Option<f64> {
+        if self.periods.is_empty() {
+            return None;
+        }
+        Some(self.periods.iter().sum::<f64>() / self.periods.len() as f64)
+    }
+
+    /// Mean height and period of the highest 1 / n of the waves
+    fn highest_fraction(&self, n: usize) -> Option<(f64, f64)> {
+        let count = self.heights.len() / n;
+        if count == 0 {
+            return None;
+        }
+
+        let mut waves = self
+            .heights
+            .iter()
+            .zip(self.periods.iter())
+            .collect::<Vec<_>>();
+        waves.sort_by(|a, b| b.0.partial_cmp(a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let (height, period) = waves
+            .iter()
+            .take(count)
+            .fold((0.0, 0.0), |acc, (h, t)| (acc.0 + *h, acc.1 + *t));
+        Some((height / count as f64, period / count as f64))
+    }
+}
+
+/// Splits a surface elevation series in meters sampled every dt seconds into individual waves
+/// using zero up-crossings of its mean level. Crossing times are linearly interpolated between
+/// samples, and the partial waves before the first and after the last crossing are dropped
+pub fn zero_crossing_analysis(elevation: &[f64], dt: f64) -> ZeroCrossingAnalysis {
+    let mut heights = Vec::new();
+    let mut periods = Vec::new();
+
+    if elevation.is_empty() || dt <= 0.0 {
+        return ZeroCrossingAnalysis { heights, periods };
+    }
+
+    let mean = elevation.iter().sum::<f64>() / elevation.len() as f64;
+    let eta = elevation.iter().map(|e| e - mean).collect::<Vec<f64>>();
+
+    let crossings = (1..eta.len())
+        .filter(|i| eta[i - 1] < 0.0 && eta[*i] >= 0.0)
+        .map(|i| {
+            let fraction = -eta[i - 1] / (eta[i] - eta[i - 1]);
+            (i, (i - 1) as f64 * dt + fraction * dt)
+        })
+        .collect::<Vec<_>>();
+
+    for window in crossings.windows(2) {
+        let (start, start_time) = window[0];
+        let (end, end_time) = window[1];
+
+        let wave = &eta[start..end];
+        let crest = wave.iter().cloned().fold(f64::MIN, f64::max);
+        let trough = wave.iter().cloned().fold(f64::MAX, f64::min);
+        heights.push(crest - trough);
+        periods.push(end_time - start_time);
+    }
+
+    ZeroCrossingAnalysis { heights, periods }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::zero_crossing_analysis;
+    use crate::tools::{
+        parametric::SpectralShape,
+        simulation::random_phase_surface,
+        statistics::{spectral_moment, test_frequency_spectrum},
+    };
+
+    #[test]
+    fn test_zero_crossing_sine() {
+        let dt = 0.1;
+        let elevation = (0..1000)
+            .map(|i| 0.5 * (2.0 * PI * i as f64 * dt / 10.0 - 1.0).sin())
+            .collect::<Vec<f64>>();
+
+        let analysis = zero_crossing_analysis(&elevation, dt);
+        assert_eq!(analysis.wave_count(), 9);
+        assert!(analysis.periods.iter().all(|t| (t - 10.0).abs() < 1e-6));
+        assert!((analysis.hmax().unwrap() - 1.0).abs() < 0.01);
+        assert!((analysis.h1_3().unwrap() - 1.0).abs() < 0.01);
+        assert!((analysis.mean_period().unwrap() - 10.0).abs() < 1e-6);
+
+        let empty = zero_crossing_analysis(&[0.0; 10], dt);
+        assert_eq!(empty.wave_count(), 0);
+        assert!(empty.hmax().is_none());
+        assert!(empty.h1_3().is_none());
+    }
+
+    #[test]
+    fn test_zero_crossing_random_sea() {
+        let (frequency, energy, df) =
+            test_frequency_spectrum(&SpectralShape::Jonswap { gamma: 3.3 }, 200);
+        let m0 = spectral_moment(&frequency, &energy, &df, 0);
+        let hm0 = 4.0 * m0.sqrt();
+
+        let elevation = random_phase_surface(&frequency, &energy, &df, 3.0 * 3600.0, 0.25, Some(3));
+        let analysis = zero_crossing_analysis(&elevation, 0.25);
+
+        // H1/3 of a real sea is slightly lower than Hm0, and Hmax of a three hour storm
+        // is roughly 1.6 to 2 times H1/3
+        let h1_3 = analysis.h1_3().unwrap();
+        assert!(h1_3 / hm0 > 0.85 && h1_3 / hm0 < 1.05);
+        let ratio = analysis.hmax().unwrap() / h1_3;
+        assert!(ratio > 1.4 && ratio < 2.3);
+        assert!(analysis.h1_10().unwrap() > h1_3);
+        assert!(analysis.t1_3().unwrap() > analysis.mean_period().unwrap());
+    }
+}
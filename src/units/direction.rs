@@ -24,6 +24,13 @@ impl DirectionConvention {
             DirectionConvention::Met => ((270.0 - dir) + 360.0) % 360.0,
         }
     }
+
+    /// Converts a direction in degrees using the From convention to this convention
+    pub fn denormalize(&self, dir: f64) -> f64 {
+        // Flipping by 180 and reflecting about 270 are both their own inverse, so converting
+        // back from the From convention is the same operation as normalizing
+        self.normalize(dir)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
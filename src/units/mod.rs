@@ -30,6 +30,10 @@ pub enum Unit {
     Seconds,
     Percent,
     KiloJoules,
+    Hertz,
+    KiloWattsPerMeter,
+    JoulesPerSquareMeter,
+    Dimensionless,
     Unknown,
 }
 
@@ -55,6 +59,10 @@ impl Unit {
             Unit::Seconds => "s",
             Unit::Percent => "%",
             Unit::KiloJoules => "kJ",
+            Unit::Hertz => "Hz",
+            Unit::KiloWattsPerMeter => "kW/m",
+            Unit::JoulesPerSquareMeter => "J/m²",
+            Unit::Dimensionless => "",
             Unit::Unknown => "",
         }
     }
@@ -80,6 +88,10 @@ impl Unit {
             Unit::Seconds => "seconds",
             Unit::Percent => "percent",
             Unit::KiloJoules => "kilojoules",
+            Unit::Hertz => "hertz",
+            Unit::KiloWattsPerMeter => "kilowatts per meter",
+            Unit::JoulesPerSquareMeter => "joules per square meter",
+            Unit::Dimensionless => "dimensionless",
             Unit::Unknown => "unknown",
         }
     }
@@ -111,6 +123,10 @@ impl From<&str> for Unit {
             "s" | "second" | "seconds" => Unit::Seconds,
             "%" | "percent" | "percentage" | "wmounit:percent" => Unit::Percent,
             "kj" | "kilojoules" | "kilojoule" => Unit::KiloJoules,
+            "hz" | "hertz" | "s-1" => Unit::Hertz,
+            "kw/m" | "kwm-1" | "kilowattspermeter" => Unit::KiloWattsPerMeter,
+            "j/m^2" | "j/m2" | "jm-2" | "joulespersquaremeter" => Unit::JoulesPerSquareMeter,
+            "1" | "dimensionless" => Unit::Dimensionless,
             _ => Unit::Unknown,
         }
     }
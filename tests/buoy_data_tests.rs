@@ -6,7 +6,9 @@ use rayon::prelude::*;
 use std::collections::HashMap;
 use std::f64::consts::PI;
 use std::fs;
-use surfrs::data::directional_spectral_wave_data_record::DirectionalSpectralWaveDataRecord;
+use surfrs::data::directional_spectral_wave_data_record::{
+    DirectionalEstimator, DirectionalSpectralWaveDataRecord,
+};
 use surfrs::data::forecast_cbulletin_wave_data_record::{
     ForecastCBulletinWaveRecord, ForecastCBulletinWaveRecordCollection,
 };
@@ -18,10 +20,12 @@ use surfrs::data::meteorological_data_record::{
 use surfrs::data::spectral_wave_data_record::SpectralWaveDataRecordCollection;
 use surfrs::data::swden_wave_data_record::SwdenWaveDataRecordCollection;
 use surfrs::data::wave_data_record::WaveDataRecordCollection;
+use surfrs::spectra_time_series::SpectraTimeSeries;
 use surfrs::swell::{Swell, SwellProvider};
+use surfrs::tools::partition::PartitionConfig;
 use surfrs::tools::vector::bin;
 use surfrs::tools::waves::track_partitions;
-use surfrs::units::{UnitConvertible, UnitSystem};
+use surfrs::units::{Unit, UnitConvertible, UnitSystem};
 
 fn read_mock_data(name: &str) -> String {
     fs::read_to_string(format!("mock/{}", name)).unwrap()
@@ -149,6 +153,65 @@ fn read_wave_spectra_data() {
     let _binned_cart_e = bin(&cart_e, &min_e, &max_e, &255);
 }
 
+#[test]
+fn track_wave_spectra_time_series() {
+    let raw_energy_data = read_mock_data("44097.data_spec");
+    let raw_mean_wave_direction_data = read_mock_data("44097.swdir");
+    let raw_primary_wave_direction_data = read_mock_data("44097.swdir2");
+    let raw_first_polar_coefficient_data = read_mock_data("44097.swr1");
+    let raw_second_polar_coefficient_data = read_mock_data("44097.swr2");
+
+    let mut energy_data_collection =
+        SpectralWaveDataRecordCollection::from_data(raw_energy_data.as_str());
+    let mut mean_wave_direction_data_collection =
+        SpectralWaveDataRecordCollection::from_data(&raw_mean_wave_direction_data.as_str());
+    let mut primary_wave_direction_data_collection =
+        SpectralWaveDataRecordCollection::from_data(&raw_primary_wave_direction_data.as_str());
+    let mut first_polar_coefficient_collection =
+        SpectralWaveDataRecordCollection::from_data(&raw_first_polar_coefficient_data.as_str());
+    let mut second_polar_coefficient_collection =
+        SpectralWaveDataRecordCollection::from_data(&raw_second_polar_coefficient_data.as_str());
+
+    let dir_count = 36usize;
+    let dir_step = (2.0 * PI) / dir_count as f64;
+    let directions = (0..dir_count)
+        .map(|i| dir_step * (i as f64))
+        .collect::<Vec<f64>>();
+
+    let series = SpectraTimeSeries::from_data_records(
+        &directions,
+        energy_data_collection.records().take(12),
+        mean_wave_direction_data_collection.records().take(12),
+        primary_wave_direction_data_collection.records().take(12),
+        first_polar_coefficient_collection.records().take(12),
+        second_polar_coefficient_collection.records().take(12),
+        &DirectionalEstimator::FourierSeries,
+    )
+    .unwrap();
+    assert_eq!(series.len(), 12);
+
+    let config = PartitionConfig::watershed(100, Some(0.8));
+    let tracked = series.track_swell(&config, true, 30.0, 1e6).unwrap();
+    assert_eq!(tracked.len(), 12);
+    assert!(tracked
+        .iter()
+        .all(|s| s.components.iter().all(|c| c.partition.is_some())));
+}
+
+#[test]
+fn cbulletin_forecast_wave_power() {
+    let raw_data = read_mock_data("gfswave.44097.cbull");
+    let mut data_collection = ForecastCBulletinWaveRecordCollection::from_data(raw_data.as_str());
+    let bulletin_records: Vec<ForecastCBulletinWaveRecord> =
+        data_collection.records().unwrap().1.collect();
+
+    // Wave power is available for every timestep of the forecast
+    assert!(bulletin_records
+        .iter()
+        .map(|r| r.wave_power(None))
+        .all(|p| p.get_value() > 0.0 && p.unit == Unit::KiloWattsPerMeter));
+}
+
 #[test]
 fn read_cbulletin_forecast_station_data() {
     let raw_data = read_mock_data("gfswave.44097.cbull");
@@ -4,6 +4,7 @@ use surfrs::{
     data::nws_weather_forecast_data_record::{
         NwsGridPointData, NwsWeatherForecastDataRecordCollection,
     },
+    tools::wave_growth::WaveGrowthModel,
     units::{CardinalDirection, Unit},
 };
 
@@ -53,4 +54,12 @@ fn read_hourly_forecast_data() {
         .map(|d| d.cardinal_direction())
         .unwrap_or(&CardinalDirection::Invalid);
     assert!(wind_direction_value == &CardinalDirection::East);
+
+    // 6 mph easterly over a 20 km sound for six hours
+    let swell = records[0]
+        .wind_wave_growth(20.0e3, 6.0 * 3600.0, &WaveGrowthModel::Smb)
+        .unwrap();
+    assert_eq!(swell.wave_height.unit, Unit::Meters);
+    assert!(swell.wave_height.value.unwrap() > 0.0 && swell.wave_height.value.unwrap() < 0.5);
+    assert_eq!(swell.direction.value.unwrap().degrees, 90);
 }